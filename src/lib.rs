@@ -34,22 +34,91 @@ pub fn delete_bookmark(req: store::DeleteReq) -> Result<String, napi::Error> {
 
 /// 保存书签（创建或更新）
 #[napi]
-pub fn save_bookmark(bookmark: store::Bookmark) -> Result<f64, napi::Error> {
-    match store::save_bookmark(bookmark) {
+pub fn save_bookmark(req: store::BookmarkDataReq) -> Result<f64, napi::Error> {
+    match store::save_bookmark(req) {
         Ok(id) => Ok(id as f64), // JavaScript 使用 number 类型，转换为 f64
         Err(e) => Err(napi::Error::from_reason(format!("Failed to save bookmark: {}", e))),
     }
 }
 
-/// 查询书签列表
+/// 查询书签列表，支持 keyset 游标分页
 #[napi]
-pub fn query_bookmark(req: store::BookmarkQuery) -> Result<Vec<store::Bookmark>, napi::Error> {
+pub fn query_bookmark(req: store::BookmarkQueryReq) -> Result<store::BookmarkPage, napi::Error> {
     match store::query_bookmark(req) {
         Ok(result) => Ok(result),
         Err(e) => Err(napi::Error::from_reason(format!("Failed to query bookmarks: {}", e))),
     }
 }
 
+/// 基于 FTS5 的书签全文搜索
+#[napi]
+pub fn search_bookmarks_fts(query: String, limit: Option<i32>) -> Result<Vec<store::BookmarkSearchResult>, napi::Error> {
+    match store::search_bookmarks_fts(query, limit) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to search bookmarks: {}", e))),
+    }
+}
+
+/// 从根文件夹 id 导出完整的嵌套书签树
+#[napi]
+pub fn query_bookmark_tree(root_id: i64) -> Result<store::BookmarkNode, napi::Error> {
+    match store::query_bookmark_tree(root_id) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to query bookmark tree: {}", e))),
+    }
+}
+
+/// 将一棵书签树批量导入到指定 parent/folder 下
+#[napi]
+pub fn import_bookmark_tree(parent: i64, folder: i64, tree: store::BookmarkNodeData) -> Result<f64, napi::Error> {
+    match store::import_bookmark_tree(parent, folder, tree) {
+        Ok(id) => Ok(id as f64),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to import bookmark tree: {}", e))),
+    }
+}
+
+/// 查询书签变更日志（按 log_id 升序）
+#[napi]
+pub fn query_bookmark_log(req: store::BookmarkLogQueryReq) -> Result<Vec<store::BookmarkLogEntry>, napi::Error> {
+    match store::query_bookmark_log(req) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to query bookmark log: {}", e))),
+    }
+}
+
+/// 撤销指定日志条目对应的书签变更
+#[napi]
+pub fn undo_bookmark_log(log_id: i64) -> Result<f64, napi::Error> {
+    match store::undo_bookmark_log(log_id) {
+        Ok(id) => Ok(id as f64),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to undo bookmark log: {}", e))),
+    }
+}
+
+/// 在单个事务内原子地提交一批书签操作（create/update/delete/update_if），任一操作失败则整体回滚
+#[napi]
+pub fn execute_bookmark_transaction(ops: Vec<store::BookmarkOpReq>) -> Result<Vec<f64>, napi::Error> {
+    match store::execute_bookmark_transaction(ops) {
+        Ok(ids) => Ok(ids.into_iter().map(|id| id as f64).collect()),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to execute bookmark transaction: {}", e))),
+    }
+}
+
+/// 使书签内存缓存失效并立即整表重新加载，供直接修改数据库文件的外部调用者使用
+#[napi]
+pub fn invalidate_bookmark_cache() -> Result<String, napi::Error> {
+    match store::invalidate_bookmark_cache() {
+        Ok(_) => Ok("bookmark cache invalidated successfully".to_string()),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to invalidate bookmark cache: {}", e))),
+    }
+}
+
+/// 启动后台线程，每隔指定秒数重新加载一次书签缓存，用于感知跨进程的外部修改
+#[napi]
+pub fn start_bookmark_cache_refresher(interval_secs: i64) {
+    store::start_bookmark_cache_refresher(interval_secs.max(1) as u64);
+}
+
 // 历史记录管理相关函数导出
 
 /// 保存历史记录（创建或更新）
@@ -60,3 +129,117 @@ pub fn save_history(history: store::History) -> Result<f64, napi::Error> {
         Err(e) => Err(napi::Error::from_reason(format!("Failed to save history: {}", e))),
     }
 }
+
+/// 按 frecency 查询历史记录，用于地址栏自动补全
+#[napi]
+pub fn query_history_frecent(req: store::HistoryFrecentReq) -> Result<Vec<store::History>, napi::Error> {
+    match store::query_history_frecent(req) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to query frecent history: {}", e))),
+    }
+}
+
+/// 批量重算所有历史记录的 frecency（维护任务）
+#[napi]
+pub fn recompute_all_frecency() -> Result<i64, napi::Error> {
+    match store::recompute_all_frecency() {
+        Ok(count) => Ok(count),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to recompute frecency: {}", e))),
+    }
+}
+
+/// 基于 FTS5 的历史记录全文搜索
+#[napi]
+pub fn search_history_fts(query: String, limit: Option<i32>) -> Result<Vec<store::HistorySearchResult>, napi::Error> {
+    match store::search_history_fts(query, limit) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to search history: {}", e))),
+    }
+}
+
+/// 基于 FTS5 的下载记录全文搜索
+#[napi]
+pub fn search_downloads_fts(query: String, limit: Option<i32>) -> Result<Vec<store::DownloadSearchResult>, napi::Error> {
+    match store::search_downloads_fts(query, limit) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to search downloads: {}", e))),
+    }
+}
+
+/// 记录一次导航的结构化元数据（转换类型、文档类型、referrer、停留时长、搜索词）
+#[napi]
+pub fn record_observation(obs: store::HistoryObservation) -> Result<f64, napi::Error> {
+    match store::record_observation(obs) {
+        Ok(id) => Ok(id as f64),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to record observation: {}", e))),
+    }
+}
+
+/// 按停留时长与访问新旧程度排序，返回 highlights/top-sites 候选集
+#[napi]
+pub fn query_highlights(limit: Option<i32>) -> Result<Vec<store::HistoryHighlightResult>, napi::Error> {
+    match store::query_highlights(limit) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to query highlights: {}", e))),
+    }
+}
+
+// 备份与恢复相关函数导出
+
+/// 在线备份所有数据库到目标目录
+#[napi]
+pub fn backup_all(dest_dir: String) -> Result<Vec<store::BackupStatus>, napi::Error> {
+    match store::backup_all(dest_dir) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to backup databases: {}", e))),
+    }
+}
+
+/// 从备份目录恢复所有数据库
+#[napi]
+pub fn restore_all(src_dir: String) -> Result<Vec<store::BackupStatus>, napi::Error> {
+    match store::restore_all(src_dir) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to restore databases: {}", e))),
+    }
+}
+
+// 同步相关函数导出
+
+/// 获取自指定时间戳以来的书签/历史变更，用于跨设备同步
+#[napi]
+pub fn changes_since(timestamp: i64) -> Result<Vec<store::SyncRecord>, napi::Error> {
+    match store::changes_since(timestamp) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to compute changes: {}", e))),
+    }
+}
+
+/// 合并远端同步记录到本地存储
+#[napi]
+pub fn apply_remote(records: Vec<store::SyncRecord>) -> Result<String, napi::Error> {
+    match store::apply_remote(records) {
+        Ok(_) => Ok("remote changes applied successfully".to_string()),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to apply remote changes: {}", e))),
+    }
+}
+
+// 导入相关函数导出
+
+/// 从其他浏览器（chrome/firefox）导入历史记录
+#[napi]
+pub fn import_history(path: String, source: String) -> Result<store::ImportResult, napi::Error> {
+    match store::import_history(path, source) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to import history: {}", e))),
+    }
+}
+
+/// 从其他浏览器（chrome/firefox）导入书签
+#[napi]
+pub fn import_bookmarks(path: String, source: String) -> Result<store::ImportResult, napi::Error> {
+    match store::import_bookmarks(path, source) {
+        Ok(result) => Ok(result),
+        Err(e) => Err(napi::Error::from_reason(format!("Failed to import bookmarks: {}", e))),
+    }
+}
@@ -1,27 +1,102 @@
 use anyhow::Error;
 use napi_derive::napi;
-use rusqlite::Connection;
 use sea_query::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{OnceLock, RwLock};
 
-use crate::store::{base_path, execute_simple, execute_transaction, open_conn, DeleteReq, GetReq};
+use crate::store::{
+    base_path, execute_simple, execute_transaction, fts_prefix_query, open_conn, DbPool, DeleteReq, GetReq, DEFAULT_POOL_MAX_SIZE,
+};
 
 // 模块级别的数据库连接
-static BOOKMARK_CONNECTION: OnceLock<Arc<Mutex<Connection>>> = OnceLock::new();
+static BOOKMARK_CONNECTION: OnceLock<DbPool> = OnceLock::new();
+
+fn bookmark_database_path() -> PathBuf {
+    let base_path = base_path().unwrap_or("");
+    PathBuf::from(base_path).join("bookmark.db")
+}
 
 /// 获取书签数据库连接
-fn connection() -> &'static Arc<Mutex<Connection>> {
+pub(crate) fn connection() -> &'static DbPool {
     BOOKMARK_CONNECTION.get_or_init(|| {
-        let base_path = base_path().unwrap_or("");
-        let database_path = PathBuf::from(base_path).join("bookmark.db");
-        open_conn(database_path.to_str().unwrap()).expect("Failed to create bookmark database connection")
+        open_conn(bookmark_database_path().to_str().unwrap()).expect("Failed to create bookmark database connection")
     })
 }
 
+/// 丢弃当前连接池并基于磁盘上的最新文件重新打开，同时使内存缓存失效；
+/// 供 restore 成功替换 bookmark.db 文件后调用，使已初始化的进程内状态看到恢复后的数据
+pub(crate) fn reset_connection() -> Result<(), Error> {
+    connection().reset(bookmark_database_path().to_str().unwrap(), DEFAULT_POOL_MAX_SIZE)?;
+    if BOOKMARK_CACHE_LOADED.get().is_some() {
+        reload_bookmark_cache()?;
+    }
+    Ok(())
+}
+
+// 全量书签的常驻内存缓存（id -> Bookmark），首次访问时懒加载
+static BOOKMARK_CACHE: OnceLock<RwLock<HashMap<i64, Bookmark>>> = OnceLock::new();
+static BOOKMARK_CACHE_LOADED: OnceLock<()> = OnceLock::new();
+
+fn bookmark_cache() -> &'static RwLock<HashMap<i64, Bookmark>> {
+    BOOKMARK_CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// 从数据库整表重建缓存，用于首次加载、外部修改数据库文件后的手动失效，以及后台定时刷新
+fn reload_bookmark_cache() -> Result<(), Error> {
+    let rows = execute_simple(connection(), |conn| {
+        let sql = Query::select().columns(BOOKMARK_COLUMNS).from(BookmarkTable::Table).to_string(SqliteQueryBuilder);
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], read_bookmark)?;
+
+        let mut bookmarks = Vec::new();
+        for row in rows {
+            bookmarks.push(row?);
+        }
+        Ok(bookmarks)
+    })?;
+
+    let mut cache = bookmark_cache().write().expect("bookmark cache lock poisoned");
+    cache.clear();
+    for bookmark in rows {
+        cache.insert(bookmark.id, bookmark);
+    }
+    Ok(())
+}
+
+fn ensure_bookmark_cache_loaded() {
+    BOOKMARK_CACHE_LOADED.get_or_init(|| {
+        reload_bookmark_cache().expect("Failed to warm bookmark cache");
+    });
+}
+
+/// 供直接修改数据库文件的外部调用者使缓存失效，并立即从数据库整表重新加载
+pub fn invalidate_bookmark_cache() -> Result<(), Error> {
+    reload_bookmark_cache()
+}
+
+/// 仅在缓存已经被加载过的前提下才使其失效重载；供 sync::apply_remote、import::import_bookmarks
+/// 这类绕过 save_bookmark/delete_bookmark、直接对书签表执行原始 SQL 的写入路径使用，
+/// 使缓存不会在同步/导入后继续提供过期或已被远端删除的数据，同时避免对尚未被访问过的
+/// 缓存提前触发一次没有意义的加载
+pub(crate) fn invalidate_bookmark_cache_if_loaded() -> Result<(), Error> {
+    if BOOKMARK_CACHE_LOADED.get().is_some() {
+        reload_bookmark_cache()?;
+    }
+    Ok(())
+}
+
+/// 启动一个后台线程，每隔 `interval_secs` 秒重新加载一次缓存，用于感知绕过本进程发生的外部修改
+pub fn start_bookmark_cache_refresher(interval_secs: u64) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(interval_secs));
+        let _ = reload_bookmark_cache();
+    });
+}
+
 #[derive(Iden)]
-enum BookmarkTable {
+pub(crate) enum BookmarkTable {
     Table,
     Id,
     Sort,
@@ -31,6 +106,46 @@ enum BookmarkTable {
     Name,
     Icon,
     Date,
+    Guid,
+    LastModified,
+}
+
+#[derive(Iden)]
+enum BookmarkLogTable {
+    Table,
+    LogId,
+    BookmarkId,
+    Operation,
+    Timestamp,
+    Reason,
+    PreviousData,
+    NewData,
+}
+
+/// 书签变更日志记录的操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BookmarkLogOp {
+    Create,
+    Update,
+    Delete,
+}
+
+impl BookmarkLogOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BookmarkLogOp::Create => "create",
+            BookmarkLogOp::Update => "update",
+            BookmarkLogOp::Delete => "delete",
+        }
+    }
+
+    fn from_str(s: &str) -> BookmarkLogOp {
+        match s {
+            "create" => BookmarkLogOp::Create,
+            "delete" => BookmarkLogOp::Delete,
+            _ => BookmarkLogOp::Update,
+        }
+    }
 }
 
 #[napi(object)]
@@ -44,6 +159,8 @@ pub struct Bookmark {
     pub name: String,
     pub icon: String,
     pub date: i64,
+    pub guid: String,
+    pub last_modified: i64,
 }
 
 /// 书签数据结构（不包含id，用于创建和更新）
@@ -65,6 +182,8 @@ pub struct BookmarkData {
 pub struct BookmarkDataReq {
     pub id: Option<i64>, // None表示创建，Some表示更新
     pub data: BookmarkData,
+    pub reason: Option<String>, // 操作原因，记录到变更日志
+    pub dedupe: Option<bool>, // 创建时若同一 folder 下已存在相同规范化 URL，则返回已有行 id 而非新建
 }
 
 /// 书签查询请求结构
@@ -73,16 +192,33 @@ pub struct BookmarkDataReq {
 pub struct BookmarkQueryReq {
     // 查询过滤条件
     pub url: Option<String>,
+    pub url_prefix: Option<String>, // 编译为 url LIKE 'prefix%'，区别于 url 的子串匹配
     pub name: Option<String>,
     pub folder: Option<i64>,
     pub parent: Option<i64>,
-    // 分页和排序
-    pub page: Option<i32>,
-    pub page_size: Option<i32>,
+    // keyset 分页：传入上一页最后一行的 (sort, id)，配合稳定的 ORDER BY sort, id 向后翻页
+    pub after: Option<BookmarkCursor>,
+    pub limit: Option<i32>,
     pub order_by: Option<String>,
     pub order_desc: Option<bool>,
 }
 
+/// keyset 分页游标，编码上一页最后一行的 (sort, id)
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BookmarkCursor {
+    pub sort: i64,
+    pub id: i64,
+}
+
+/// 书签分页查询结果，`next_cursor` 为 `None` 表示已到达末尾
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkPage {
+    pub items: Vec<Bookmark>,
+    pub next_cursor: Option<BookmarkCursor>,
+}
+
 /// 初始化表
 pub fn init_bookmark_database() -> Result<(), Error> {
     execute_simple(connection(), |conn| {
@@ -103,46 +239,209 @@ pub fn init_bookmark_database() -> Result<(), Error> {
             .col(ColumnDef::new(BookmarkTable::Name).text().not_null())
             .col(ColumnDef::new(BookmarkTable::Icon).text().not_null())
             .col(ColumnDef::new(BookmarkTable::Date).integer().not_null())
+            .col(ColumnDef::new(BookmarkTable::Guid).text().not_null().unique_key().default(""))
+            .col(ColumnDef::new(BookmarkTable::LastModified).big_integer().not_null().default(0))
             .to_string(SqliteQueryBuilder);
         conn.execute(&sql, [])?;
 
+        conn.execute(
+            &Table::create()
+                .table(BookmarkLogTable::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(BookmarkLogTable::LogId)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(BookmarkLogTable::BookmarkId).integer().not_null())
+                .col(ColumnDef::new(BookmarkLogTable::Operation).text().not_null())
+                .col(ColumnDef::new(BookmarkLogTable::Timestamp).big_integer().not_null())
+                .col(ColumnDef::new(BookmarkLogTable::Reason).text())
+                .col(ColumnDef::new(BookmarkLogTable::PreviousData).text())
+                .col(ColumnDef::new(BookmarkLogTable::NewData).text())
+                .to_string(SqliteQueryBuilder),
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_bookmark_log_bookmark_id ON bookmark_log(bookmark_id)",
+            [],
+        )?;
+
+        init_bookmark_fts(conn)?;
+
         Ok(())
     })
 }
 
-/// 获取书签
+/// 将字符串内部连续的空白折叠为单个空格
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 规范化 URL 的 scheme 与 host 部分为小写，路径/查询部分保持原样（遵循 Chromium 书签模型的大小写处理规则）。
+/// 空白/空字符串会被规范化为 `""`：这不是遗漏的校验，而是本 schema 沿用 Chromium 书签模型的既定约定——
+/// 文件夹节点没有独立的 `is_folder` 标记，而是以 `url == ""` 来表示，因此这里必须保留而不是拒绝空 URL，
+/// 否则会破坏文件夹的创建。
+fn normalize_bookmark_url(url: &str) -> String {
+    if url.trim().is_empty() {
+        return String::new();
+    }
+
+    match url.find("://") {
+        Some(scheme_end) => {
+            let scheme = &url[..scheme_end];
+            let rest = &url[scheme_end + 3..];
+            let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+            let (host, tail) = rest.split_at(host_end);
+            format!("{}://{}{}", scheme.to_lowercase(), host.to_lowercase(), tail)
+        }
+        None => url.to_string(),
+    }
+}
+
+fn find_duplicate_bookmark_in(conn: &rusqlite::Connection, url: &str, folder: i64) -> Result<Option<Bookmark>, Error> {
+    Ok(conn
+        .query_row(
+            &Query::select()
+                .columns(BOOKMARK_COLUMNS)
+                .from(BookmarkTable::Table)
+                .and_where(Expr::col(BookmarkTable::Url).eq(url))
+                .and_where(Expr::col(BookmarkTable::Folder).eq(folder))
+                .to_string(SqliteQueryBuilder),
+            [],
+            read_bookmark,
+        )
+        .ok())
+}
+
+/// 查找同一 folder 下是否已存在相同规范化 URL 的书签，用于保存前去重
+pub fn find_duplicate_bookmark(url: String, folder: i64) -> Result<Option<Bookmark>, Error> {
+    execute_simple(connection(), |conn| find_duplicate_bookmark_in(conn, &url, folder))
+}
+
+fn to_bookmark_data(bookmark: &Bookmark) -> BookmarkData {
+    BookmarkData {
+        sort: bookmark.sort,
+        folder: bookmark.folder,
+        parent: bookmark.parent,
+        url: bookmark.url.clone(),
+        name: bookmark.name.clone(),
+        icon: bookmark.icon.clone(),
+        date: bookmark.date,
+    }
+}
+
+/// 在同一事务内追加一条变更日志，记录操作前后的 BookmarkData 快照
+fn record_bookmark_log(
+    conn: &rusqlite::Connection,
+    bookmark_id: i64,
+    op: BookmarkLogOp,
+    reason: Option<&str>,
+    previous: Option<&BookmarkData>,
+    new: Option<&BookmarkData>,
+) -> Result<i64, Error> {
+    let table = BookmarkLogTable::Table.to_string();
+    let previous_json = previous.map(serde_json::to_string).transpose()?;
+    let new_json = new.map(serde_json::to_string).transpose()?;
+
+    conn.execute(
+        &format!(
+            "INSERT INTO {table} (bookmark_id, operation, timestamp, reason, previous_data, new_data) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+        ),
+        rusqlite::params![bookmark_id, op.as_str(), crate::store::sync::now_ms(), reason, previous_json, new_json],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+pub(crate) fn read_bookmark(row: &rusqlite::Row) -> rusqlite::Result<Bookmark> {
+    Ok(Bookmark {
+        id: row.get(0)?,
+        sort: row.get(1)?,
+        folder: row.get(2)?,
+        parent: row.get(3)?,
+        url: row.get(4)?,
+        name: row.get(5)?,
+        icon: row.get(6)?,
+        date: row.get(7)?,
+        guid: row.get(8)?,
+        last_modified: row.get(9)?,
+    })
+}
+
+pub(crate) const BOOKMARK_COLUMNS: [BookmarkTable; 10] = [
+    BookmarkTable::Id,
+    BookmarkTable::Sort,
+    BookmarkTable::Folder,
+    BookmarkTable::Parent,
+    BookmarkTable::Url,
+    BookmarkTable::Name,
+    BookmarkTable::Icon,
+    BookmarkTable::Date,
+    BookmarkTable::Guid,
+    BookmarkTable::LastModified,
+];
+
+/// 创建 bookmark 的 FTS5 外部内容表，并用触发器保持与主表同步
+fn init_bookmark_fts(conn: &rusqlite::Connection) -> Result<(), Error> {
+    let table = BookmarkTable::Table.to_string();
+
+    conn.execute(
+        &format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS bookmark_fts USING fts5(
+                name, url, content='{table}', content_rowid='id'
+            )"
+        ),
+        [],
+    )?;
+
+    conn.execute(
+        &format!(
+            "CREATE TRIGGER IF NOT EXISTS bookmark_fts_ai AFTER INSERT ON {table} BEGIN
+                INSERT INTO bookmark_fts(rowid, name, url) VALUES (new.id, new.name, new.url);
+            END"
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!(
+            "CREATE TRIGGER IF NOT EXISTS bookmark_fts_ad AFTER DELETE ON {table} BEGIN
+                INSERT INTO bookmark_fts(bookmark_fts, rowid, name, url) VALUES ('delete', old.id, old.name, old.url);
+            END"
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!(
+            "CREATE TRIGGER IF NOT EXISTS bookmark_fts_au AFTER UPDATE ON {table} BEGIN
+                INSERT INTO bookmark_fts(bookmark_fts, rowid, name, url) VALUES ('delete', old.id, old.name, old.url);
+                INSERT INTO bookmark_fts(rowid, name, url) VALUES (new.id, new.name, new.url);
+            END"
+        ),
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 获取书签：命中常驻内存缓存则直接返回，否则回退到 SQLite 查询
 pub fn get_bookmark(req: GetReq) -> Result<Option<Bookmark>, Error> {
+    ensure_bookmark_cache_loaded();
+    if let Some(bookmark) = bookmark_cache().read().expect("bookmark cache lock poisoned").get(&req.id) {
+        return Ok(Some(bookmark.clone()));
+    }
+
     execute_simple(connection(), |conn| {
         let sql = Query::select()
-            .columns([
-                BookmarkTable::Id,
-                BookmarkTable::Sort,
-                BookmarkTable::Folder,
-                BookmarkTable::Parent,
-                BookmarkTable::Url,
-                BookmarkTable::Name,
-                BookmarkTable::Icon,
-                BookmarkTable::Date,
-            ])
+            .columns(BOOKMARK_COLUMNS)
             .from(BookmarkTable::Table)
             .and_where(Expr::col(BookmarkTable::Id).eq(req.id))
             .to_string(SqliteQueryBuilder);
 
         let mut stmt = conn.prepare(&sql).expect("Failed to prepare query");
-        let mut rows = stmt
-            .query_map([], |row| {
-                Ok(Bookmark {
-                    id: row.get(0)?,
-                    sort: row.get(1)?,
-                    folder: row.get(2)?,
-                    parent: row.get(3)?,
-                    url: row.get(4)?,
-                    name: row.get(5)?,
-                    icon: row.get(6)?,
-                    date: row.get(7)?,
-                })
-            })
-            .expect("Failed to execute query");
+        let mut rows = stmt.query_map([], read_bookmark).expect("Failed to execute query");
 
         match rows.next() {
             Some(bookmark) => Ok(Some(bookmark?)),
@@ -151,24 +450,74 @@ pub fn get_bookmark(req: GetReq) -> Result<Option<Bookmark>, Error> {
     })
 }
 
-/// 删除书签
+/// 删除书签，同时写入 tombstone 以便同步端感知删除，并记录一条变更日志供撤销/同步使用
 pub fn delete_bookmark(req: DeleteReq) -> Result<(), Error> {
     execute_transaction(connection(), |conn| {
+        let existing = conn
+            .query_row(
+                &Query::select()
+                    .columns(BOOKMARK_COLUMNS)
+                    .from(BookmarkTable::Table)
+                    .and_where(Expr::col(BookmarkTable::Id).eq(req.id))
+                    .to_string(SqliteQueryBuilder),
+                [],
+                read_bookmark,
+            )
+            .ok();
+
         let sql = Query::delete()
             .from_table(BookmarkTable::Table)
             .and_where(Expr::col(BookmarkTable::Id).eq(req.id))
             .to_string(SqliteQueryBuilder);
 
         conn.execute(&sql, []).expect("Failed to execute delete");
+
+        if let Some(bookmark) = &existing {
+            crate::store::sync::record_tombstone(conn, "bookmark", &bookmark.guid)?;
+        }
+
+        record_bookmark_log(
+            conn,
+            req.id,
+            BookmarkLogOp::Delete,
+            req.reason.as_deref(),
+            existing.as_ref().map(to_bookmark_data).as_ref(),
+            None,
+        )?;
+
         Ok(())
-    })
+    })?;
+
+    if BOOKMARK_CACHE_LOADED.get().is_some() {
+        bookmark_cache().write().expect("bookmark cache lock poisoned").remove(&req.id);
+    }
+    Ok(())
 }
 
-/// 创建或更新书签（统一接口）
-pub fn save_bookmark(req: BookmarkDataReq) -> Result<i64, Error> {
-    execute_transaction(connection(), |conn| {
+/// 创建或更新书签（统一接口），并在同一事务内记录一条变更日志供撤销/同步使用
+pub fn save_bookmark(mut req: BookmarkDataReq) -> Result<i64, Error> {
+    // 遵循 Chromium 书签模型的空白处理规则：trim 首尾空白、折叠 name 内部空白、规范化 URL 大小写
+    req.data.name = collapse_whitespace(req.data.name.trim());
+    req.data.url = normalize_bookmark_url(req.data.url.trim());
+
+    let id = execute_transaction(connection(), |conn| {
+        let now = crate::store::sync::now_ms();
+
         if let Some(id) = req.id {
             // 更新操作
+            let previous = conn
+                .query_row(
+                    &Query::select()
+                        .columns(BOOKMARK_COLUMNS)
+                        .from(BookmarkTable::Table)
+                        .and_where(Expr::col(BookmarkTable::Id).eq(id))
+                        .to_string(SqliteQueryBuilder),
+                    [],
+                    read_bookmark,
+                )
+                .ok()
+                .map(|b| to_bookmark_data(&b));
+
             let sql = Query::update()
                 .table(BookmarkTable::Table)
                 .values([
@@ -179,14 +528,25 @@ pub fn save_bookmark(req: BookmarkDataReq) -> Result<i64, Error> {
                     (BookmarkTable::Name, req.data.name.clone().into()),
                     (BookmarkTable::Icon, req.data.icon.clone().into()),
                     (BookmarkTable::Date, req.data.date.into()),
+                    (BookmarkTable::LastModified, now.into()),
                 ])
                 .and_where(Expr::col(BookmarkTable::Id).eq(id))
                 .to_string(SqliteQueryBuilder);
 
             conn.execute(&sql, []).expect("Failed to execute update");
+
+            record_bookmark_log(conn, id, BookmarkLogOp::Update, req.reason.as_deref(), previous.as_ref(), Some(&req.data))?;
+
             Ok(id)
         } else {
-            // 创建操作
+            // 创建操作：若启用 dedupe 且同一 folder 下已存在相同规范化 URL 的书签，直接返回该行 id
+            if req.dedupe.unwrap_or(false) {
+                if let Some(existing) = find_duplicate_bookmark_in(conn, &req.data.url, req.data.folder)? {
+                    return Ok(existing.id);
+                }
+            }
+
+            let guid = crate::store::sync::generate_guid();
             let sql = Query::insert()
                 .into_table(BookmarkTable::Table)
                 .columns([
@@ -197,40 +557,116 @@ pub fn save_bookmark(req: BookmarkDataReq) -> Result<i64, Error> {
                     BookmarkTable::Name,
                     BookmarkTable::Icon,
                     BookmarkTable::Date,
+                    BookmarkTable::Guid,
+                    BookmarkTable::LastModified,
                 ])
                 .values_panic([
                     req.data.sort.into(),
                     req.data.folder.into(),
                     req.data.parent.into(),
-                    req.data.url.into(),
-                    req.data.name.into(),
-                    req.data.icon.into(),
+                    req.data.url.clone().into(),
+                    req.data.name.clone().into(),
+                    req.data.icon.clone().into(),
                     req.data.date.into(),
+                    guid.into(),
+                    now.into(),
                 ])
                 .to_string(SqliteQueryBuilder);
 
             conn.execute(&sql, []).expect("Failed to execute create");
-            Ok(conn.last_insert_rowid())
+            let id = conn.last_insert_rowid();
+
+            record_bookmark_log(conn, id, BookmarkLogOp::Create, req.reason.as_deref(), None, Some(&req.data))?;
+
+            Ok(id)
         }
-    })
+    })?;
+
+    if BOOKMARK_CACHE_LOADED.get().is_some() {
+        refresh_cached_bookmark(id);
+    }
+    Ok(id)
+}
+
+/// 写入成功后，用数据库中的最新一行替换缓存中对应的条目，保持缓存与存储一致
+fn refresh_cached_bookmark(id: i64) {
+    let bookmark = execute_simple(connection(), |conn| {
+        conn.query_row(
+            &Query::select()
+                .columns(BOOKMARK_COLUMNS)
+                .from(BookmarkTable::Table)
+                .and_where(Expr::col(BookmarkTable::Id).eq(id))
+                .to_string(SqliteQueryBuilder),
+            [],
+            read_bookmark,
+        )
+        .ok()
+    });
+
+    let mut cache = bookmark_cache().write().expect("bookmark cache lock poisoned");
+    match bookmark {
+        Ok(Some(bookmark)) => {
+            cache.insert(id, bookmark);
+        }
+        _ => {
+            cache.remove(&id);
+        }
+    }
+}
+
+/// 给定一页结果与本次请求的 limit，在结果恰好装满一页时派生出下一页的游标
+fn next_cursor_for(bookmarks: &[Bookmark], limit: Option<i32>) -> Option<BookmarkCursor> {
+    let limit = limit?;
+    if limit > 0 && bookmarks.len() as i32 == limit {
+        bookmarks.last().map(|b| BookmarkCursor { sort: b.sort, id: b.id })
+    } else {
+        None
+    }
 }
 
 /// 查询书签列表
-pub fn query_bookmark(req: BookmarkQueryReq) -> Result<Vec<Bookmark>, Error> {
+pub fn query_bookmark(req: BookmarkQueryReq) -> Result<BookmarkPage, Error> {
+    // 书签栏渲染等高频场景只按 folder/parent/游标过滤，不涉及子串匹配，命中缓存即可避免 prepare+query 开销
+    if req.url.is_none() && req.url_prefix.is_none() && req.name.is_none() {
+        ensure_bookmark_cache_loaded();
+        let mut bookmarks: Vec<Bookmark> = bookmark_cache()
+            .read()
+            .expect("bookmark cache lock poisoned")
+            .values()
+            .filter(|b| req.folder.map_or(true, |f| b.folder == f))
+            .filter(|b| req.parent.map_or(true, |p| b.parent == p))
+            .cloned()
+            .collect();
+
+        if let Some(after) = req.after {
+            // keyset 分页要求单一稳定顺序，游标翻页时忽略自定义 order_by
+            bookmarks.sort_by_key(|b| (b.sort, b.id));
+            bookmarks.retain(|b| (b.sort, b.id) > (after.sort, after.id));
+        } else {
+            match req.order_by.as_deref() {
+                Some("name") => bookmarks.sort_by(|a, b| a.name.cmp(&b.name)),
+                Some("date") => bookmarks.sort_by_key(|b| b.date),
+                _ => bookmarks.sort_by_key(|b| (b.sort, b.id)),
+            }
+            if req.order_desc.unwrap_or(false) {
+                bookmarks.reverse();
+            }
+        }
+
+        if let Some(limit) = req.limit {
+            bookmarks.truncate(limit.max(0) as usize);
+        }
+        let next_cursor = next_cursor_for(&bookmarks, req.limit);
+
+        return Ok(BookmarkPage {
+            items: bookmarks,
+            next_cursor,
+        });
+    }
+
     execute_simple(connection(), |conn| {
         let mut query = Query::select();
-        query
-            .columns([
-                BookmarkTable::Id,
-                BookmarkTable::Sort,
-                BookmarkTable::Folder,
-                BookmarkTable::Parent,
-                BookmarkTable::Url,
-                BookmarkTable::Name,
-                BookmarkTable::Icon,
-                BookmarkTable::Date,
-            ])
-            .from(BookmarkTable::Table);
+        query.columns(BOOKMARK_COLUMNS).from(BookmarkTable::Table);
 
         // 应用过滤条件
         for (field, column) in [
@@ -251,9 +687,25 @@ pub fn query_bookmark(req: BookmarkQueryReq) -> Result<Vec<Bookmark>, Error> {
                 }
             }
         }
+        if let Some(prefix) = &req.url_prefix {
+            if !prefix.is_empty() {
+                query.and_where(Expr::col(BookmarkTable::Url).like(format!("{}%", prefix)));
+            }
+        }
 
-        // 应用排序
-        if let Some(order_by) = &req.order_by {
+        if let Some(after) = &req.after {
+            // keyset 分页：WHERE (sort, id) > (?, ?)，按 sort > ? OR (sort = ? AND id > ?) 展开，配合稳定的 ORDER BY sort, id 向后翻页
+            query.cond_where(
+                Condition::any()
+                    .add(Expr::col(BookmarkTable::Sort).gt(after.sort))
+                    .add(
+                        Condition::all()
+                            .add(Expr::col(BookmarkTable::Sort).eq(after.sort))
+                            .add(Expr::col(BookmarkTable::Id).gt(after.id)),
+                    ),
+            );
+            query.order_by(BookmarkTable::Sort, Order::Asc).order_by(BookmarkTable::Id, Order::Asc);
+        } else if let Some(order_by) = &req.order_by {
             let order = if req.order_desc.unwrap_or(false) {
                 Order::Desc
             } else {
@@ -266,41 +718,541 @@ pub fn query_bookmark(req: BookmarkQueryReq) -> Result<Vec<Bookmark>, Error> {
                 _ => query.order_by(BookmarkTable::Sort, Order::Asc),
             };
         } else {
-            query.order_by(BookmarkTable::Sort, Order::Asc);
+            query.order_by(BookmarkTable::Sort, Order::Asc).order_by(BookmarkTable::Id, Order::Asc);
         }
 
-        // 应用分页
-        if let (Some(page), Some(page_size)) = (req.page, req.page_size) {
-            let offset = (page - 1) * page_size;
-            query.limit(page_size as u64).offset(offset as u64);
+        if let Some(limit) = req.limit {
+            query.limit(limit.max(0) as u64);
         }
 
         let sql = query.to_string(SqliteQueryBuilder);
         let mut stmt = conn.prepare(&sql).expect("Failed to prepare query");
-        let rows = stmt
-            .query_map([], |row| {
-                Ok(Bookmark {
-                    id: row.get(0)?,
-                    sort: row.get(1)?,
-                    folder: row.get(2)?,
-                    parent: row.get(3)?,
-                    url: row.get(4)?,
-                    name: row.get(5)?,
-                    icon: row.get(6)?,
-                    date: row.get(7)?,
-                })
-            })
-            .expect("Failed to execute query");
+        let rows = stmt.query_map([], read_bookmark).expect("Failed to execute query");
 
         let mut bookmarks = Vec::new();
         for row in rows {
             bookmarks.push(row?);
         }
 
-        Ok(bookmarks)
+        let next_cursor = next_cursor_for(&bookmarks, req.limit);
+        Ok(BookmarkPage {
+            items: bookmarks,
+            next_cursor,
+        })
+    })
+}
+
+/// FTS5 搜索结果，附带 bm25 相关度得分（越大越相关）
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkSearchResult {
+    pub bookmark: Bookmark,
+    pub score: f64,
+}
+
+/// 基于 FTS5 的书签搜索，支持前缀匹配并按相关度排序
+pub fn search_bookmarks_fts(query: String, limit: Option<i32>) -> Result<Vec<BookmarkSearchResult>, Error> {
+    execute_simple(connection(), |conn| {
+        let match_query = fts_prefix_query(&query);
+        let table = BookmarkTable::Table.to_string();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT b.id, b.sort, b.folder, b.parent, b.url, b.name, b.icon, b.date, b.guid, b.last_modified, bm25(bookmark_fts) AS rank
+             FROM bookmark_fts
+             JOIN {table} b ON b.id = bookmark_fts.rowid
+             WHERE bookmark_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2"
+        ))?;
+
+        let rows = stmt.query_map(rusqlite::params![&match_query, limit.unwrap_or(20)], |row| {
+            Ok(BookmarkSearchResult {
+                bookmark: read_bookmark(row)?,
+                score: -row.get::<_, f64>(10)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    })
+}
+
+/// 从根节点开始递归组装的嵌套书签树，用于整棵文件夹的导出/备份
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkNode {
+    pub bookmark: Bookmark,
+    pub children: Vec<BookmarkNode>,
+}
+
+fn build_bookmark_tree(
+    node_id: i64,
+    adjacency: &std::collections::HashMap<i64, Vec<Bookmark>>,
+    by_id: &std::collections::HashMap<i64, Bookmark>,
+    visited: &mut std::collections::HashSet<i64>,
+) -> Result<BookmarkNode, Error> {
+    if !visited.insert(node_id) {
+        return Err(anyhow::anyhow!("cycle detected while building bookmark tree at id {}", node_id));
+    }
+
+    let bookmark = by_id
+        .get(&node_id)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("bookmark {} not found", node_id))?;
+
+    let mut children = Vec::new();
+    if let Some(child_rows) = adjacency.get(&node_id) {
+        for child in child_rows {
+            children.push(build_bookmark_tree(child.id, adjacency, by_id, visited)?);
+        }
+    }
+
+    visited.remove(&node_id);
+    Ok(BookmarkNode { bookmark, children })
+}
+
+/// 给定根文件夹 id，递归组装其完整的嵌套书签树（单次 SELECT + 内存装配，避免 N+1 查询）
+pub fn query_bookmark_tree(root_id: i64) -> Result<BookmarkNode, Error> {
+    execute_simple(connection(), |conn| {
+        let sql = Query::select()
+            .columns(BOOKMARK_COLUMNS)
+            .from(BookmarkTable::Table)
+            .order_by(BookmarkTable::Parent, Order::Asc)
+            .order_by(BookmarkTable::Sort, Order::Asc)
+            .to_string(SqliteQueryBuilder);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], read_bookmark)?;
+
+        let mut adjacency: std::collections::HashMap<i64, Vec<Bookmark>> = std::collections::HashMap::new();
+        let mut by_id: std::collections::HashMap<i64, Bookmark> = std::collections::HashMap::new();
+        for row in rows {
+            let bookmark = row?;
+            by_id.insert(bookmark.id, bookmark.clone());
+            adjacency.entry(bookmark.parent).or_default().push(bookmark);
+        }
+
+        let mut visited = std::collections::HashSet::new();
+        build_bookmark_tree(root_id, &adjacency, &by_id, &mut visited)
+    })
+}
+
+/// 批量导入时使用的书签节点（不含 id/guid，由插入时分配）
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkNodeData {
+    pub data: BookmarkData,
+    pub children: Vec<BookmarkNodeData>,
+}
+
+fn insert_bookmark_node(conn: &rusqlite::Connection, node: &BookmarkNodeData, parent: i64, folder: i64) -> Result<i64, Error> {
+    let guid = crate::store::sync::generate_guid();
+    let now = crate::store::sync::now_ms();
+
+    conn.execute(
+        &Query::insert()
+            .into_table(BookmarkTable::Table)
+            .columns([
+                BookmarkTable::Sort,
+                BookmarkTable::Folder,
+                BookmarkTable::Parent,
+                BookmarkTable::Url,
+                BookmarkTable::Name,
+                BookmarkTable::Icon,
+                BookmarkTable::Date,
+                BookmarkTable::Guid,
+                BookmarkTable::LastModified,
+            ])
+            .values_panic([
+                node.data.sort.into(),
+                folder.into(),
+                parent.into(),
+                node.data.url.clone().into(),
+                node.data.name.clone().into(),
+                node.data.icon.clone().into(),
+                node.data.date.into(),
+                guid.into(),
+                now.into(),
+            ])
+            .to_string(SqliteQueryBuilder),
+        [],
+    )?;
+
+    let id = conn.last_insert_rowid();
+    for child in &node.children {
+        insert_bookmark_node(conn, child, id, folder)?;
+    }
+    Ok(id)
+}
+
+/// 深度优先批量导入一棵书签树，挂载到给定 parent/folder 下，并将每个子节点的 parent 重写为新分配的行 id
+pub fn import_bookmark_tree(parent: i64, folder: i64, tree: BookmarkNodeData) -> Result<i64, Error> {
+    execute_transaction(connection(), |conn| insert_bookmark_node(conn, &tree, parent, folder)).inspect(|_| {
+        if BOOKMARK_CACHE_LOADED.get().is_some() {
+            let _ = reload_bookmark_cache();
+        }
     })
 }
 
+/// 一条书签变更日志，附带操作前后的 BookmarkData JSON 快照
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkLogEntry {
+    pub log_id: i64,
+    pub bookmark_id: i64,
+    pub operation: String,
+    pub timestamp: i64,
+    pub reason: Option<String>,
+    pub previous_data: Option<String>,
+    pub new_data: Option<String>,
+}
+
+/// `query_bookmark_log` 请求参数
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkLogQueryReq {
+    pub since_log_id: Option<i64>,
+    pub bookmark_id: Option<i64>,
+    pub limit: Option<i32>,
+}
+
+/// 按 log_id 升序返回变更日志，可按起始 log_id 与 bookmark_id 过滤
+pub fn query_bookmark_log(req: BookmarkLogQueryReq) -> Result<Vec<BookmarkLogEntry>, Error> {
+    execute_simple(connection(), |conn| {
+        let mut query = Query::select();
+        query
+            .columns([
+                BookmarkLogTable::LogId,
+                BookmarkLogTable::BookmarkId,
+                BookmarkLogTable::Operation,
+                BookmarkLogTable::Timestamp,
+                BookmarkLogTable::Reason,
+                BookmarkLogTable::PreviousData,
+                BookmarkLogTable::NewData,
+            ])
+            .from(BookmarkLogTable::Table);
+
+        if let Some(since) = req.since_log_id {
+            query.and_where(Expr::col(BookmarkLogTable::LogId).gt(since));
+        }
+        if let Some(bookmark_id) = req.bookmark_id {
+            query.and_where(Expr::col(BookmarkLogTable::BookmarkId).eq(bookmark_id));
+        }
+
+        query
+            .order_by(BookmarkLogTable::LogId, Order::Asc)
+            .limit(req.limit.unwrap_or(100).max(0) as u64);
+
+        let sql = query.to_string(SqliteQueryBuilder);
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok(BookmarkLogEntry {
+                log_id: row.get(0)?,
+                bookmark_id: row.get(1)?,
+                operation: row.get(2)?,
+                timestamp: row.get(3)?,
+                reason: row.get(4)?,
+                previous_data: row.get(5)?,
+                new_data: row.get(6)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    })
+}
+
+/// `update_if` 检测到期望值与当前存储值不一致时返回的冲突错误，可通过 `Error::downcast_ref` 识别
+#[derive(Debug)]
+pub struct ConflictError {
+    pub id: i64,
+}
+
+impl std::fmt::Display for ConflictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bookmark {} was modified since it was last read", self.id)
+    }
+}
+
+impl std::error::Error for ConflictError {}
+
+/// 批量事务中累积的单个操作
+enum BookmarkTxOp {
+    Create(BookmarkData),
+    Update(i64, BookmarkData),
+    Delete(i64),
+    UpdateIf(i64, BookmarkData, BookmarkData),
+}
+
+/// 累积一批书签操作，在单个事务内全部提交或全部回滚，用于批量重排序等需要原子性的场景
+#[derive(Default)]
+pub struct BookmarkTransaction {
+    ops: Vec<BookmarkTxOp>,
+}
+
+impl BookmarkTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(mut self, data: BookmarkData) -> Self {
+        self.ops.push(BookmarkTxOp::Create(data));
+        self
+    }
+
+    pub fn update(mut self, id: i64, data: BookmarkData) -> Self {
+        self.ops.push(BookmarkTxOp::Update(id, data));
+        self
+    }
+
+    pub fn delete(mut self, id: i64) -> Self {
+        self.ops.push(BookmarkTxOp::Delete(id));
+        self
+    }
+
+    /// 仅当 `id` 当前存储的数据与 `expected` 相等时才应用 `new`，否则整个事务以 ConflictError 回滚
+    pub fn update_if(mut self, id: i64, expected: BookmarkData, new: BookmarkData) -> Self {
+        self.ops.push(BookmarkTxOp::UpdateIf(id, expected, new));
+        self
+    }
+
+    /// 在单个 `execute_transaction` 内按顺序提交所有累积的操作，任一操作失败则整体回滚，返回每个操作结果的 id
+    pub fn commit(self) -> Result<Vec<i64>, Error> {
+        execute_transaction(connection(), |conn| {
+            let mut ids = Vec::with_capacity(self.ops.len());
+
+            for op in &self.ops {
+                match op {
+                    BookmarkTxOp::Create(data) => {
+                        let guid = crate::store::sync::generate_guid();
+                        let now = crate::store::sync::now_ms();
+                        conn.execute(
+                            &Query::insert()
+                                .into_table(BookmarkTable::Table)
+                                .columns([
+                                    BookmarkTable::Sort,
+                                    BookmarkTable::Folder,
+                                    BookmarkTable::Parent,
+                                    BookmarkTable::Url,
+                                    BookmarkTable::Name,
+                                    BookmarkTable::Icon,
+                                    BookmarkTable::Date,
+                                    BookmarkTable::Guid,
+                                    BookmarkTable::LastModified,
+                                ])
+                                .values_panic([
+                                    data.sort.into(),
+                                    data.folder.into(),
+                                    data.parent.into(),
+                                    data.url.clone().into(),
+                                    data.name.clone().into(),
+                                    data.icon.clone().into(),
+                                    data.date.into(),
+                                    guid.into(),
+                                    now.into(),
+                                ])
+                                .to_string(SqliteQueryBuilder),
+                            [],
+                        )?;
+                        let id = conn.last_insert_rowid();
+                        record_bookmark_log(conn, id, BookmarkLogOp::Create, None, None, Some(data))?;
+                        ids.push(id);
+                    }
+                    BookmarkTxOp::Update(id, data) => {
+                        let previous = conn
+                            .query_row(
+                                &Query::select()
+                                    .columns(BOOKMARK_COLUMNS)
+                                    .from(BookmarkTable::Table)
+                                    .and_where(Expr::col(BookmarkTable::Id).eq(*id))
+                                    .to_string(SqliteQueryBuilder),
+                                [],
+                                read_bookmark,
+                            )
+                            .ok()
+                            .map(|b| to_bookmark_data(&b));
+
+                        apply_bookmark_update(conn, *id, data)?;
+                        record_bookmark_log(conn, *id, BookmarkLogOp::Update, None, previous.as_ref(), Some(data))?;
+                        ids.push(*id);
+                    }
+                    BookmarkTxOp::Delete(id) => {
+                        let existing = conn
+                            .query_row(
+                                &Query::select()
+                                    .columns(BOOKMARK_COLUMNS)
+                                    .from(BookmarkTable::Table)
+                                    .and_where(Expr::col(BookmarkTable::Id).eq(*id))
+                                    .to_string(SqliteQueryBuilder),
+                                [],
+                                read_bookmark,
+                            )
+                            .ok();
+
+                        conn.execute(
+                            &Query::delete()
+                                .from_table(BookmarkTable::Table)
+                                .and_where(Expr::col(BookmarkTable::Id).eq(*id))
+                                .to_string(SqliteQueryBuilder),
+                            [],
+                        )?;
+
+                        if let Some(bookmark) = &existing {
+                            crate::store::sync::record_tombstone(conn, "bookmark", &bookmark.guid)?;
+                        }
+
+                        record_bookmark_log(conn, *id, BookmarkLogOp::Delete, None, existing.as_ref().map(to_bookmark_data).as_ref(), None)?;
+                        ids.push(*id);
+                    }
+                    BookmarkTxOp::UpdateIf(id, expected, new) => {
+                        let current = conn
+                            .query_row(
+                                &Query::select()
+                                    .columns(BOOKMARK_COLUMNS)
+                                    .from(BookmarkTable::Table)
+                                    .and_where(Expr::col(BookmarkTable::Id).eq(*id))
+                                    .to_string(SqliteQueryBuilder),
+                                [],
+                                read_bookmark,
+                            )
+                            .map(|b| to_bookmark_data(&b))
+                            .map_err(|_| anyhow::anyhow!(ConflictError { id: *id }))?;
+
+                        if !bookmark_data_eq(&current, expected) {
+                            return Err(anyhow::anyhow!(ConflictError { id: *id }));
+                        }
+
+                        apply_bookmark_update(conn, *id, new)?;
+                        record_bookmark_log(conn, *id, BookmarkLogOp::Update, None, Some(&current), Some(new))?;
+                        ids.push(*id);
+                    }
+                }
+            }
+
+            Ok(ids)
+        })
+        .inspect(|_| {
+            if BOOKMARK_CACHE_LOADED.get().is_some() {
+                let _ = reload_bookmark_cache();
+            }
+        })
+    }
+}
+
+fn bookmark_data_eq(a: &BookmarkData, b: &BookmarkData) -> bool {
+    a.sort == b.sort && a.folder == b.folder && a.parent == b.parent && a.url == b.url && a.name == b.name && a.icon == b.icon && a.date == b.date
+}
+
+fn apply_bookmark_update(conn: &rusqlite::Connection, id: i64, data: &BookmarkData) -> Result<(), Error> {
+    conn.execute(
+        &Query::update()
+            .table(BookmarkTable::Table)
+            .values([
+                (BookmarkTable::Sort, data.sort.into()),
+                (BookmarkTable::Folder, data.folder.into()),
+                (BookmarkTable::Parent, data.parent.into()),
+                (BookmarkTable::Url, data.url.clone().into()),
+                (BookmarkTable::Name, data.name.clone().into()),
+                (BookmarkTable::Icon, data.icon.clone().into()),
+                (BookmarkTable::Date, data.date.into()),
+                (BookmarkTable::LastModified, crate::store::sync::now_ms().into()),
+            ])
+            .and_where(Expr::col(BookmarkTable::Id).eq(id))
+            .to_string(SqliteQueryBuilder),
+        [],
+    )?;
+    Ok(())
+}
+
+/// 批量事务中的单个操作（跨 FFI 边界传输用，`kind` 取值为 "create"/"update"/"delete"/"update_if"）
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkOpReq {
+    pub kind: String,
+    pub id: Option<i64>,
+    pub data: Option<BookmarkData>,
+    pub expected: Option<BookmarkData>,
+}
+
+/// 将一批操作累积进 `BookmarkTransaction` 并一次性提交，任一操作失败（含 `update_if` 冲突）则整体回滚
+pub fn execute_bookmark_transaction(ops: Vec<BookmarkOpReq>) -> Result<Vec<i64>, Error> {
+    let mut tx = BookmarkTransaction::new();
+
+    for op in ops {
+        tx = match op.kind.as_str() {
+            "create" => tx.create(op.data.ok_or_else(|| anyhow::anyhow!("create op requires data"))?),
+            "update" => tx.update(
+                op.id.ok_or_else(|| anyhow::anyhow!("update op requires id"))?,
+                op.data.ok_or_else(|| anyhow::anyhow!("update op requires data"))?,
+            ),
+            "delete" => tx.delete(op.id.ok_or_else(|| anyhow::anyhow!("delete op requires id"))?),
+            "update_if" => tx.update_if(
+                op.id.ok_or_else(|| anyhow::anyhow!("update_if op requires id"))?,
+                op.expected.ok_or_else(|| anyhow::anyhow!("update_if op requires expected"))?,
+                op.data.ok_or_else(|| anyhow::anyhow!("update_if op requires data"))?,
+            ),
+            other => return Err(anyhow::anyhow!("unknown bookmark transaction op kind: {}", other)),
+        };
+    }
+
+    tx.commit()
+}
+
+/// 回放指定日志条目的逆操作：撤销创建=删除该行，撤销更新=恢复上一版本，撤销删除=按快照重新插入
+pub fn undo_bookmark_log(log_id: i64) -> Result<i64, Error> {
+    let (bookmark_id, operation, previous_data) = execute_simple(connection(), |conn| {
+        let sql = Query::select()
+            .columns([BookmarkLogTable::BookmarkId, BookmarkLogTable::Operation, BookmarkLogTable::PreviousData])
+            .from(BookmarkLogTable::Table)
+            .and_where(Expr::col(BookmarkLogTable::LogId).eq(log_id))
+            .to_string(SqliteQueryBuilder);
+
+        Ok(conn.query_row(&sql, [], |row| {
+            let bookmark_id: i64 = row.get(0)?;
+            let operation: String = row.get(1)?;
+            let previous_data: Option<String> = row.get(2)?;
+            Ok((bookmark_id, operation, previous_data))
+        })?)
+    })?;
+
+    let reason = Some(format!("undo of log #{log_id}"));
+
+    match BookmarkLogOp::from_str(&operation) {
+        BookmarkLogOp::Create => {
+            delete_bookmark(DeleteReq {
+                id: bookmark_id,
+                force: None,
+                cascade: None,
+                reason,
+            })?;
+            Ok(bookmark_id)
+        }
+        BookmarkLogOp::Update => {
+            let data: BookmarkData = serde_json::from_str(
+                &previous_data.ok_or_else(|| anyhow::anyhow!("log #{} is missing its previous snapshot", log_id))?,
+            )?;
+            save_bookmark(BookmarkDataReq {
+                id: Some(bookmark_id),
+                data,
+                reason,
+                dedupe: None,
+            })
+        }
+        BookmarkLogOp::Delete => {
+            let data: BookmarkData = serde_json::from_str(
+                &previous_data.ok_or_else(|| anyhow::anyhow!("log #{} is missing its previous snapshot", log_id))?,
+            )?;
+            save_bookmark(BookmarkDataReq { id: None, data, reason, dedupe: None })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,11 +1303,12 @@ mod tests {
     fn create_simple_query() -> BookmarkQueryReq {
         BookmarkQueryReq {
             url: None,
+            url_prefix: None,
             name: None,
             folder: None,
             parent: None,
-            page: None,
-            page_size: None,
+            after: None,
+            limit: None,
             order_by: None,
             order_desc: None,
         }
@@ -374,6 +1327,8 @@ mod tests {
         let bookmark_id = save_bookmark(BookmarkDataReq {
             id: None,
             data: bookmark_data.clone(),
+            reason: None,
+            dedupe: None,
         })
         .unwrap();
 
@@ -395,6 +1350,8 @@ mod tests {
         let bookmark_id = save_bookmark(BookmarkDataReq {
             id: None,
             data: bookmark_data,
+            reason: None,
+            dedupe: None,
         })
         .unwrap();
 
@@ -404,6 +1361,7 @@ mod tests {
             id: bookmark_id,
             force: None,
             cascade: None,
+            reason: None,
         })
         .unwrap();
 
@@ -423,13 +1381,13 @@ mod tests {
 
         for (name, url, folder, parent) in bookmarks_data.iter() {
             let data = create_test_bookmark_data(name, url, *folder, *parent);
-            save_bookmark(BookmarkDataReq { id: None, data }).unwrap();
+            save_bookmark(BookmarkDataReq { id: None, data, reason: None, dedupe: None }).unwrap();
         }
 
         // 测试按 folder 查询
         let mut query = create_simple_query();
         query.folder = Some(1);
-        let bookmarks = query_bookmark(query).unwrap();
+        let bookmarks = query_bookmark(query).unwrap().items;
         assert!(bookmarks.len() >= 2);
         assert!(bookmarks.iter().any(|b| b.name == "Bookmark 1" && b.folder == 1));
         assert!(bookmarks.iter().any(|b| b.name == "Bookmark 2" && b.folder == 1));
@@ -437,7 +1395,7 @@ mod tests {
         // 测试按 parent 查询
         let mut query = create_simple_query();
         query.parent = Some(20);
-        let bookmarks = query_bookmark(query).unwrap();
+        let bookmarks = query_bookmark(query).unwrap().items;
         assert!(bookmarks.len() >= 1);
         assert!(bookmarks.iter().any(|b| b.name == "Test Bookmark" && b.parent == 20));
 
@@ -445,7 +1403,7 @@ mod tests {
         let mut query = create_simple_query();
         query.folder = Some(1);
         query.parent = Some(10);
-        let bookmarks = query_bookmark(query).unwrap();
+        let bookmarks = query_bookmark(query).unwrap().items;
         assert!(bookmarks.len() >= 2);
         assert!(bookmarks
             .iter()
@@ -457,21 +1415,21 @@ mod tests {
         // 测试按 name 模糊查询
         let mut query = create_simple_query();
         query.name = Some("Test".to_string());
-        let bookmarks = query_bookmark(query).unwrap();
+        let bookmarks = query_bookmark(query).unwrap().items;
         assert!(bookmarks.len() >= 1);
         assert!(bookmarks.iter().any(|b| b.name == "Test Bookmark"));
 
         // 测试按 url 模糊查询
         let mut query = create_simple_query();
         query.url = Some("example".to_string());
-        let bookmarks = query_bookmark(query).unwrap();
+        let bookmarks = query_bookmark(query).unwrap().items;
         assert!(bookmarks.len() >= 2);
         assert!(bookmarks.iter().any(|b| b.url.contains("example1.com")));
         assert!(bookmarks.iter().any(|b| b.url.contains("example2.com")));
 
         // 测试查询所有书签
         let query = create_simple_query();
-        let bookmarks = query_bookmark(query).unwrap();
+        let bookmarks = query_bookmark(query).unwrap().items;
         assert!(bookmarks.len() >= 3);
     }
 
@@ -483,6 +1441,8 @@ mod tests {
         let bookmark_id = save_bookmark(BookmarkDataReq {
             id: None,
             data: bookmark_data,
+            reason: None,
+            dedupe: None,
         })
         .unwrap();
 
@@ -497,6 +1457,8 @@ mod tests {
         let bookmark_id = save_bookmark(BookmarkDataReq {
             id: None,
             data: bookmark_data.clone(),
+            reason: None,
+            dedupe: None,
         })
         .unwrap();
 
@@ -513,6 +1475,8 @@ mod tests {
         save_bookmark(BookmarkDataReq {
             id: Some(bookmark_id),
             data: updated_data,
+            reason: None,
+            dedupe: None,
         })
         .unwrap();
 
@@ -522,4 +1486,297 @@ mod tests {
         assert_eq!(updated_bookmark.name, "Updated Bookmark");
         assert_eq!(updated_bookmark.url, "https://updated.com");
     }
+
+    #[test]
+    fn test_bookmark_tree_export_import() {
+        init_bookmark().expect("Initialization failed");
+
+        let root_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Root Folder", "", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+
+        let child_tree = BookmarkNodeData {
+            data: create_test_bookmark_data("Child Folder", "", 0, 0),
+            children: vec![BookmarkNodeData {
+                data: create_test_bookmark_data("Grandchild", "https://grandchild.example.com", 0, 0),
+                children: vec![],
+            }],
+        };
+        import_bookmark_tree(root_id, root_id, child_tree).unwrap();
+
+        let tree = query_bookmark_tree(root_id).unwrap();
+        assert_eq!(tree.bookmark.id, root_id);
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].bookmark.name, "Child Folder");
+        assert_eq!(tree.children[0].children.len(), 1);
+        assert_eq!(tree.children[0].children[0].bookmark.name, "Grandchild");
+        assert_eq!(tree.children[0].children[0].bookmark.folder, root_id);
+    }
+
+    #[test]
+    fn test_bookmark_log_and_undo() {
+        init_bookmark().expect("Initialization failed");
+
+        let bookmark_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Log Test", "https://log-test.example.com", 0, 0),
+            reason: Some("initial create".to_string()),
+            dedupe: None,
+        })
+        .unwrap();
+
+        let updated_data = create_test_bookmark_data("Log Test Renamed", "https://log-test.example.com", 0, 0);
+        save_bookmark(BookmarkDataReq {
+            id: Some(bookmark_id),
+            data: updated_data,
+            reason: Some("rename".to_string()),
+            dedupe: None,
+        })
+        .unwrap();
+
+        let log = query_bookmark_log(BookmarkLogQueryReq {
+            since_log_id: None,
+            bookmark_id: Some(bookmark_id),
+            limit: None,
+        })
+        .unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].operation, "create");
+        assert_eq!(log[1].operation, "update");
+        assert_eq!(log[1].reason.as_deref(), Some("rename"));
+
+        // 撤销更新应恢复到改名前的名称
+        undo_bookmark_log(log[1].log_id).unwrap();
+        let restored = get_bookmark(GetReq { id: bookmark_id }).unwrap().expect("bookmark should exist");
+        assert_eq!(restored.name, "Log Test");
+
+        // 撤销创建应删除该书签
+        undo_bookmark_log(log[0].log_id).unwrap();
+        assert!(get_bookmark(GetReq { id: bookmark_id }).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bookmark_transaction_commit_and_rollback() {
+        init_bookmark().expect("Initialization failed");
+
+        let existing_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Tx Existing", "https://tx-existing.example.com", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+
+        let ids = BookmarkTransaction::new()
+            .create(create_test_bookmark_data("Tx Created", "https://tx-created.example.com", 0, 0))
+            .update(existing_id, create_test_bookmark_data("Tx Existing Renamed", "https://tx-existing.example.com", 0, 0))
+            .commit()
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+        let created_id = ids[0];
+
+        assert_eq!(
+            get_bookmark(GetReq { id: existing_id }).unwrap().unwrap().name,
+            "Tx Existing Renamed"
+        );
+        assert!(get_bookmark(GetReq { id: created_id }).unwrap().is_some());
+
+        // update_if 冲突应回滚整批操作，包括本应成功的 delete
+        let stale_expected = create_test_bookmark_data("Tx Existing", "https://tx-existing.example.com", 0, 0);
+        let result = BookmarkTransaction::new()
+            .delete(created_id)
+            .update_if(existing_id, stale_expected, create_test_bookmark_data("Should Not Apply", "https://tx-existing.example.com", 0, 0))
+            .commit();
+        assert!(result.is_err());
+
+        // 回滚后两行应保持提交前的状态
+        assert!(get_bookmark(GetReq { id: created_id }).unwrap().is_some());
+        assert_eq!(
+            get_bookmark(GetReq { id: existing_id }).unwrap().unwrap().name,
+            "Tx Existing Renamed"
+        );
+    }
+
+    #[test]
+    fn test_bookmark_cache_read_through_and_invalidate() {
+        init_bookmark().expect("Initialization failed");
+
+        let bookmark_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Cache Test", "https://cache-test.example.com", 7, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+
+        // 首次 get_bookmark 触发缓存加载，随后应能从缓存命中到刚写入的行
+        let cached = get_bookmark(GetReq { id: bookmark_id }).unwrap().expect("bookmark should exist");
+        assert_eq!(cached.name, "Cache Test");
+        assert!(bookmark_cache().read().unwrap().contains_key(&bookmark_id));
+
+        // 按 folder 过滤的无分页查询走缓存读路径
+        let mut query = create_simple_query();
+        query.folder = Some(7);
+        let bookmarks = query_bookmark(query).unwrap().items;
+        assert!(bookmarks.iter().any(|b| b.id == bookmark_id));
+
+        // 绕过 save_bookmark 直接改库后，显式失效应让缓存反映最新数据
+        execute_simple(connection(), |conn| {
+            conn.execute(
+                &Query::update()
+                    .table(BookmarkTable::Table)
+                    .values([(BookmarkTable::Name, "Changed Externally".into())])
+                    .and_where(Expr::col(BookmarkTable::Id).eq(bookmark_id))
+                    .to_string(SqliteQueryBuilder),
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        invalidate_bookmark_cache().unwrap();
+        let refreshed = get_bookmark(GetReq { id: bookmark_id }).unwrap().expect("bookmark should exist");
+        assert_eq!(refreshed.name, "Changed Externally");
+    }
+
+    #[test]
+    fn test_save_bookmark_normalizes_whitespace_and_url_case() {
+        init_bookmark().expect("Initialization failed");
+
+        let bookmark_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("  Messy   Name  ", "  HTTPS://Example.COM/Some/Path  ", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+
+        let saved = get_bookmark(GetReq { id: bookmark_id }).unwrap().expect("bookmark should exist");
+        assert_eq!(saved.name, "Messy Name");
+        assert_eq!(saved.url, "https://example.com/Some/Path");
+
+        // 文件夹（url 为空字符串）应被规范化为保留空字符串，而非报错
+        let folder_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("  Spaced Folder  ", "   ", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+        let folder = get_bookmark(GetReq { id: folder_id }).unwrap().expect("folder should exist");
+        assert_eq!(folder.url, "");
+    }
+
+    #[test]
+    fn test_save_bookmark_dedupe_returns_existing_id() {
+        init_bookmark().expect("Initialization failed");
+
+        let first_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Dedupe Target", "https://dedupe.example.com/", 3, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+
+        // 同一 folder 下、规范化后相同 URL（大小写/空白不同）应返回已有 id 而非新建
+        let second_id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Dedupe Target Again", "  HTTPS://DEDUPE.example.com/  ", 3, 0),
+            reason: None,
+            dedupe: Some(true),
+        })
+        .unwrap();
+        assert_eq!(second_id, first_id);
+
+        let duplicate = find_duplicate_bookmark("https://dedupe.example.com/".to_string(), 3).unwrap();
+        assert_eq!(duplicate.unwrap().id, first_id);
+    }
+
+    #[test]
+    fn test_query_bookmark_keyset_pagination_and_url_prefix() {
+        init_bookmark().expect("Initialization failed");
+
+        // 用进程内唯一的 folder id 隔离本测试写入的数据，避免并行运行的其它用例
+        // 共享同一个 /tmp 数据库文件与全局 BOOKMARK_CACHE 时互相污染这里的严格计数/顺序断言
+        let folder = crate::store::sync::now_ms();
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let mut data = create_test_bookmark_data(&format!("Page Item {i}"), &format!("https://paged.example.com/{i}"), folder, 0);
+            data.sort = i;
+            ids.push(save_bookmark(BookmarkDataReq { id: None, data, reason: None, dedupe: None }).unwrap());
+        }
+
+        let mut query = create_simple_query();
+        query.folder = Some(folder);
+        query.limit = Some(2);
+        let first_page = query_bookmark(query).unwrap();
+        assert_eq!(first_page.items.len(), 2);
+        assert_eq!(first_page.items[0].name, "Page Item 0");
+        assert_eq!(first_page.items[1].name, "Page Item 1");
+        let cursor = first_page.next_cursor.expect("should have a next cursor");
+
+        let mut query = create_simple_query();
+        query.folder = Some(folder);
+        query.limit = Some(2);
+        query.after = Some(cursor);
+        let second_page = query_bookmark(query).unwrap();
+        assert_eq!(second_page.items.len(), 2);
+        assert_eq!(second_page.items[0].name, "Page Item 2");
+        assert_eq!(second_page.items[1].name, "Page Item 3");
+
+        let mut query = create_simple_query();
+        query.folder = Some(folder);
+        query.limit = Some(2);
+        query.after = second_page.next_cursor;
+        let third_page = query_bookmark(query).unwrap();
+        assert_eq!(third_page.items.len(), 1);
+        assert_eq!(third_page.items[0].name, "Page Item 4");
+        assert!(third_page.next_cursor.is_none());
+
+        // url_prefix 应按前缀匹配（区别于子串匹配的 url 过滤）
+        let mut query = create_simple_query();
+        query.url_prefix = Some("https://paged.example.com/".to_string());
+        let prefixed = query_bookmark(query).unwrap().items;
+        assert!(prefixed.len() >= 5);
+        assert!(prefixed.iter().all(|b| b.url.starts_with("https://paged.example.com/")));
+    }
+
+    #[test]
+    fn test_search_bookmarks_fts_prefix_match_and_ranking() {
+        init_bookmark().expect("Initialization failed");
+
+        let unique = crate::store::sync::now_ms();
+        save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data(&format!("Rustlang {unique} Guide"), "https://rust-fts-a.example.com", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+        save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data(&format!("Rustlang {unique} Rustlang {unique}"), "https://rust-fts-b.example.com", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+        save_bookmark(BookmarkDataReq {
+            id: None,
+            data: create_test_bookmark_data("Totally Unrelated", "https://rust-fts-c.example.com", 0, 0),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+
+        // "Rustl" 应以前缀匹配命中两条书签，且标题中重复出现关键词的行排序更靠前（bm25 分数更高）
+        let results = search_bookmarks_fts(format!("Rustl {unique}"), None).unwrap();
+        let matches: Vec<(usize, f64)> =
+            results.iter().map(|r| (r.bookmark.name.matches(&unique.to_string()).count(), r.score)).collect();
+        crate::store::assert_fts_prefix_match_and_ranking(&matches);
+    }
 }
@@ -1,24 +1,32 @@
 use anyhow::Error;
 use napi_derive::napi;
-use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::OnceLock;
 
-use crate::store::{base_path, execute_simple, execute_transaction, open_conn};
+use crate::store::{base_path, execute_simple, execute_transaction, fts_prefix_query, open_conn, DbPool, DEFAULT_POOL_MAX_SIZE};
 
 // 模块级别的数据库连接
-static DOWNLOAD_CONNECTION: OnceLock<Arc<Mutex<Connection>>> = OnceLock::new();
+static DOWNLOAD_CONNECTION: OnceLock<DbPool> = OnceLock::new();
+
+fn download_database_path() -> PathBuf {
+    let base_path = base_path().unwrap_or("");
+    PathBuf::from(base_path).join("download.db")
+}
 
 /// 获取下载数据库连接
-fn connection() -> &'static Arc<Mutex<Connection>> {
+fn connection() -> &'static DbPool {
     DOWNLOAD_CONNECTION.get_or_init(|| {
-        let base_path = base_path().unwrap_or("");
-        let database_path = PathBuf::from(base_path).join("download.db");
-        open_conn(database_path.to_str().unwrap()).expect("Failed to create download database connection")
+        open_conn(download_database_path().to_str().unwrap()).expect("Failed to create download database connection")
     })
 }
 
+/// 丢弃当前连接池并基于磁盘上的最新文件重新打开；
+/// 供 restore 成功替换 download.db 文件后调用，使已初始化的进程内状态看到恢复后的数据
+pub(crate) fn reset_connection() -> Result<(), Error> {
+    connection().reset(download_database_path().to_str().unwrap(), DEFAULT_POOL_MAX_SIZE)
+}
+
 #[napi(object)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Download {
@@ -60,10 +68,44 @@ pub fn init_download_database() -> Result<(), Error> {
             [],
         )?;
 
+        init_download_fts(conn)?;
+
         Ok(())
     })
 }
 
+/// 创建 download 的 FTS5 外部内容表，并用触发器保持与主表同步
+fn init_download_fts(conn: &rusqlite::Connection) -> Result<(), Error> {
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS download_fts USING fts5(
+            file_name, url, content='download', content_rowid='id'
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS download_fts_ai AFTER INSERT ON download BEGIN
+            INSERT INTO download_fts(rowid, file_name, url) VALUES (new.id, new.file_name, new.url);
+        END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS download_fts_ad AFTER DELETE ON download BEGIN
+            INSERT INTO download_fts(download_fts, rowid, file_name, url) VALUES ('delete', old.id, old.file_name, old.url);
+        END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS download_fts_au AFTER UPDATE ON download BEGIN
+            INSERT INTO download_fts(download_fts, rowid, file_name, url) VALUES ('delete', old.id, old.file_name, old.url);
+            INSERT INTO download_fts(rowid, file_name, url) VALUES (new.id, new.file_name, new.url);
+        END",
+        [],
+    )?;
+
+    Ok(())
+}
+
 /// 添加下载记录
 pub fn add_download(download: Download) -> Result<i64, Error> {
     execute_transaction(connection(), |conn| {
@@ -344,6 +386,70 @@ pub fn search_downloads(keyword: String, limit: Option<i32>) -> Result<Vec<Downl
     })
 }
 
+/// FTS5 搜索结果，附带 bm25 相关度得分（越大越相关）
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadSearchResult {
+    pub download: Download,
+    pub score: f64,
+}
+
+/// 基于 FTS5 的下载记录搜索，支持前缀匹配并按相关度排序
+pub fn search_downloads_fts(query: String, limit: Option<i32>) -> Result<Vec<DownloadSearchResult>, Error> {
+    execute_simple(connection(), |conn| {
+        let match_query = fts_prefix_query(&query);
+
+        let mut stmt = conn.prepare(
+            "SELECT d.id, d.url, d.file_name, d.file_path, d.file_size, d.downloaded_size, d.status,
+                    d.start_time, d.end_time, d.mime_type, bm25(download_fts) AS rank
+             FROM download_fts
+             JOIN download d ON d.id = download_fts.rowid
+             WHERE download_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(rusqlite::params![&match_query, limit.unwrap_or(20)], |row| {
+            Ok(DownloadSearchResult {
+                download: Download {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    file_name: row.get(2)?,
+                    file_path: row.get(3)?,
+                    file_size: row.get(4)?,
+                    downloaded_size: row.get(5)?,
+                    status: row.get(6)?,
+                    start_time: row.get(7)?,
+                    end_time: {
+                        let end_time_str: String = row.get(8)?;
+                        if end_time_str.is_empty() {
+                            None
+                        } else {
+                            Some(end_time_str.parse().unwrap_or(0))
+                        }
+                    },
+                    mime_type: {
+                        let mime_str: String = row.get(9)?;
+                        if mime_str.is_empty() {
+                            None
+                        } else {
+                            Some(mime_str)
+                        }
+                    },
+                },
+                score: -row.get::<_, f64>(10)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+
+        Ok(results)
+    })
+}
+
 /// 获取下载记录总数
 pub fn get_download_count() -> Result<i64, Error> {
     execute_simple(connection(), |conn| {
@@ -398,3 +504,52 @@ pub fn get_active_downloads() -> Result<Vec<Download>, Error> {
         Ok(downloads)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::BASE_PATH;
+
+    fn init() {
+        BASE_PATH.get().or_else(|| {
+            std::fs::create_dir_all("/tmp/browser-core/database").expect("Failed to create test directory");
+            BASE_PATH.set("/tmp/browser-core/database".to_string()).ok();
+            None
+        });
+        init_download_database().expect("Failed to initialize database");
+    }
+
+    fn test_download(url: &str, file_name: &str) -> Download {
+        Download {
+            id: 0,
+            url: url.to_string(),
+            file_name: file_name.to_string(),
+            file_path: format!("/tmp/{file_name}"),
+            file_size: 1024,
+            downloaded_size: 1024,
+            status: "completed".to_string(),
+            start_time: 0,
+            end_time: None,
+            mime_type: None,
+        }
+    }
+
+    #[test]
+    fn test_search_downloads_fts_prefix_match_and_ranking() {
+        init();
+
+        let unique = crate::store::sync::now_ms();
+        add_download(test_download("https://download-fts-a.example.com", &format!("rustlang-{unique}-guide.pdf"))).unwrap();
+        add_download(test_download(
+            "https://download-fts-b.example.com",
+            &format!("rustlang-{unique}-rustlang-{unique}.pdf"),
+        ))
+        .unwrap();
+        add_download(test_download("https://download-fts-c.example.com", "totally-unrelated.pdf")).unwrap();
+
+        let results = search_downloads_fts(format!("rustlang {unique}"), None).unwrap();
+        let matches: Vec<(usize, f64)> =
+            results.iter().map(|r| (r.download.file_name.matches(&unique.to_string()).count(), r.score)).collect();
+        crate::store::assert_fts_prefix_match_and_ranking(&matches);
+    }
+}
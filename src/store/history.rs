@@ -4,28 +4,146 @@ use rusqlite::Connection;
 use sea_query::*;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::OnceLock;
 
-use crate::store::{base_path, execute_simple, execute_transaction, open_conn};
+use crate::store::sync::now_ms;
+use crate::store::{base_path, execute_simple, execute_transaction, fts_prefix_query, open_conn, DbPool, DEFAULT_POOL_MAX_SIZE};
 
-static HISTORY_CONNECTION: OnceLock<Arc<Mutex<Connection>>> = OnceLock::new();
+static HISTORY_CONNECTION: OnceLock<DbPool> = OnceLock::new();
 
-fn connection() -> &'static Arc<Mutex<Connection>> {
+fn history_database_path() -> PathBuf {
+    let base_path = base_path().unwrap_or("");
+    PathBuf::from(base_path).join("history.db")
+}
+
+pub(crate) fn connection() -> &'static DbPool {
     HISTORY_CONNECTION.get_or_init(|| {
-        let base_path = base_path().unwrap_or("");
-        let database_path = PathBuf::from(base_path).join("history.db");
-        open_conn(database_path.to_str().unwrap()).expect("Failed to create history database connection")
+        open_conn(history_database_path().to_str().unwrap()).expect("Failed to create history database connection")
     })
 }
 
+/// 丢弃当前连接池并基于磁盘上的最新文件重新打开；
+/// 供 restore 成功替换 history.db 文件后调用，使已初始化的进程内状态看到恢复后的数据
+pub(crate) fn reset_connection() -> Result<(), Error> {
+    connection().reset(history_database_path().to_str().unwrap(), DEFAULT_POOL_MAX_SIZE)
+}
+
 #[derive(Iden)]
-enum HistoryTable {
+pub(crate) enum HistoryTable {
     Table,
     Id,
     Url,
     Icon,
     Title,
     Visit,
+    VisitCount,
+    Frecency,
+    Guid,
+    LastModified,
+}
+
+#[derive(Iden)]
+enum HistoryVisitTable {
+    Table,
+    Id,
+    HistoryId,
+    VisitTime,
+    VisitType,
+}
+
+#[derive(Iden)]
+enum HistoryMetadataTable {
+    Table,
+    Id,
+    HistoryId,
+    VisitTime,
+    VisitType,
+    DocumentType,
+    ReferrerUrl,
+    DwellTimeMs,
+    SearchTerm,
+}
+
+/// 单次访问的来源类型，用于计算 frecency 权重
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitType {
+    Typed,
+    Link,
+    Bookmark,
+    Reload,
+    Embed,
+}
+
+impl VisitType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            VisitType::Typed => "typed",
+            VisitType::Link => "link",
+            VisitType::Bookmark => "bookmark",
+            VisitType::Reload => "reload",
+            VisitType::Embed => "embed",
+        }
+    }
+
+    fn from_str(s: &str) -> VisitType {
+        match s {
+            "typed" => VisitType::Typed,
+            "bookmark" => VisitType::Bookmark,
+            "reload" => VisitType::Reload,
+            "embed" => VisitType::Embed,
+            _ => VisitType::Link,
+        }
+    }
+
+    /// Mozilla Places 风格的访问类型权重
+    fn weight(&self) -> f64 {
+        match self {
+            VisitType::Typed => 2.0,
+            VisitType::Bookmark => 1.4,
+            VisitType::Link => 1.0,
+            VisitType::Reload | VisitType::Embed => 0.0,
+        }
+    }
+}
+
+/// 页面文档类型，用于区分普通页面与媒体资源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentType {
+    Regular,
+    Media,
+}
+
+impl DocumentType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::Regular => "regular",
+            DocumentType::Media => "media",
+        }
+    }
+
+    fn from_str(s: &str) -> DocumentType {
+        match s {
+            "media" => DocumentType::Media,
+            _ => DocumentType::Regular,
+        }
+    }
+}
+
+/// 按距今天数计算 Mozilla Places 风格的年龄分桶得分
+fn age_bucket_points(age_days: i64) -> f64 {
+    if age_days < 1 {
+        100.0
+    } else if age_days < 4 {
+        70.0
+    } else if age_days < 14 {
+        50.0
+    } else if age_days < 31 {
+        30.0
+    } else if age_days < 90 {
+        10.0
+    } else {
+        0.0
+    }
 }
 
 #[napi(object)]
@@ -36,6 +154,19 @@ pub struct History {
     pub icon: Option<String>,
     pub title: Option<String>,
     pub visit: Option<String>,
+    pub visit_type: Option<String>,
+    pub visit_count: Option<i64>,
+    pub frecency: Option<i64>,
+    pub guid: Option<String>,
+    pub last_modified: Option<i64>,
+}
+
+/// `query_history_frecent` 请求参数
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryFrecentReq {
+    pub query: Option<String>,
+    pub limit: Option<i32>,
 }
 
 pub fn init_history_database() -> Result<(), Error> {
@@ -49,15 +180,187 @@ pub fn init_history_database() -> Result<(), Error> {
                 .col(ColumnDef::new(HistoryTable::Icon).text())
                 .col(ColumnDef::new(HistoryTable::Title).text())
                 .col(ColumnDef::new(HistoryTable::Visit).text())
+                .col(ColumnDef::new(HistoryTable::VisitCount).integer().not_null().default(0))
+                .col(ColumnDef::new(HistoryTable::Frecency).big_integer().not_null().default(0))
+                .col(ColumnDef::new(HistoryTable::Guid).text().not_null().unique_key().default(""))
+                .col(ColumnDef::new(HistoryTable::LastModified).big_integer().not_null().default(0))
                 .to_string(SqliteQueryBuilder),
             [],
         )?;
+
+        conn.execute(
+            &Table::create()
+                .table(HistoryVisitTable::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(HistoryVisitTable::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(HistoryVisitTable::HistoryId).integer().not_null())
+                .col(ColumnDef::new(HistoryVisitTable::VisitTime).big_integer().not_null())
+                .col(ColumnDef::new(HistoryVisitTable::VisitType).text().not_null())
+                .to_string(SqliteQueryBuilder),
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_history_visit_history_id ON history_visit(history_id)",
+            [],
+        )?;
+
+        conn.execute(
+            &Table::create()
+                .table(HistoryMetadataTable::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(HistoryMetadataTable::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(HistoryMetadataTable::HistoryId).integer().not_null())
+                .col(ColumnDef::new(HistoryMetadataTable::VisitTime).big_integer().not_null())
+                .col(ColumnDef::new(HistoryMetadataTable::VisitType).text().not_null())
+                .col(ColumnDef::new(HistoryMetadataTable::DocumentType).text().not_null().default("regular"))
+                .col(ColumnDef::new(HistoryMetadataTable::ReferrerUrl).text())
+                .col(ColumnDef::new(HistoryMetadataTable::DwellTimeMs).big_integer().not_null().default(0))
+                .col(ColumnDef::new(HistoryMetadataTable::SearchTerm).text())
+                .to_string(SqliteQueryBuilder),
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_history_metadata_history_id ON history_metadata(history_id)",
+            [],
+        )?;
+
+        init_history_fts(conn)?;
+
         Ok(())
     })
 }
 
+/// 创建 history 的 FTS5 外部内容表，并用触发器保持与主表同步
+fn init_history_fts(conn: &Connection) -> Result<(), Error> {
+    let table = HistoryTable::Table.to_string();
+
+    conn.execute(
+        &format!(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+                title, url, content='{table}', content_rowid='id'
+            )"
+        ),
+        [],
+    )?;
+
+    conn.execute(
+        &format!(
+            "CREATE TRIGGER IF NOT EXISTS history_fts_ai AFTER INSERT ON {table} BEGIN
+                INSERT INTO history_fts(rowid, title, url) VALUES (new.id, new.title, new.url);
+            END"
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!(
+            "CREATE TRIGGER IF NOT EXISTS history_fts_ad AFTER DELETE ON {table} BEGIN
+                INSERT INTO history_fts(history_fts, rowid, title, url) VALUES ('delete', old.id, old.title, old.url);
+            END"
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!(
+            "CREATE TRIGGER IF NOT EXISTS history_fts_au AFTER UPDATE ON {table} BEGIN
+                INSERT INTO history_fts(history_fts, rowid, title, url) VALUES ('delete', old.id, old.title, old.url);
+                INSERT INTO history_fts(rowid, title, url) VALUES (new.id, new.title, new.url);
+            END"
+        ),
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 采样最近 10 次访问，按 Mozilla Places 的分桶规则计算 frecency
+fn compute_frecency(conn: &Connection, history_id: i64, visit_count: i64) -> Result<i64, Error> {
+    let sql = Query::select()
+        .columns([HistoryVisitTable::VisitTime, HistoryVisitTable::VisitType])
+        .from(HistoryVisitTable::Table)
+        .and_where(Expr::col(HistoryVisitTable::HistoryId).eq(history_id))
+        .order_by(HistoryVisitTable::VisitTime, Order::Desc)
+        .limit(10)
+        .to_string(SqliteQueryBuilder);
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row| {
+        let visit_time: i64 = row.get(0)?;
+        let visit_type: String = row.get(1)?;
+        Ok((visit_time, visit_type))
+    })?;
+
+    let now = now_ms();
+    let mut sum_points = 0.0;
+    let mut sampled = 0i64;
+    for row in rows {
+        let (visit_time, visit_type) = row?;
+        let age_days = (now - visit_time).max(0) / (24 * 60 * 60 * 1000);
+        sum_points += age_bucket_points(age_days) * VisitType::from_str(&visit_type).weight();
+        sampled += 1;
+    }
+
+    if sampled == 0 {
+        Ok(0)
+    } else {
+        Ok((visit_count as f64 * sum_points / sampled as f64).round() as i64)
+    }
+}
+
+fn record_visit_and_recompute(conn: &Connection, history_id: i64, visit_type: VisitType) -> Result<(i64, i64), Error> {
+    conn.execute(
+        &Query::insert()
+            .into_table(HistoryVisitTable::Table)
+            .columns([HistoryVisitTable::HistoryId, HistoryVisitTable::VisitTime, HistoryVisitTable::VisitType])
+            .values_panic([history_id.into(), now_ms().into(), visit_type.as_str().into()])
+            .to_string(SqliteQueryBuilder),
+        [],
+    )?;
+
+    let visit_count: i64 = conn.query_row(
+        &Query::select()
+            .expr(Expr::col(HistoryVisitTable::Id).count())
+            .from(HistoryVisitTable::Table)
+            .and_where(Expr::col(HistoryVisitTable::HistoryId).eq(history_id))
+            .to_string(SqliteQueryBuilder),
+        [],
+        |row| row.get(0),
+    )?;
+
+    let frecency = compute_frecency(conn, history_id, visit_count)?;
+
+    conn.execute(
+        &Query::update()
+            .table(HistoryTable::Table)
+            .values([
+                (HistoryTable::VisitCount, visit_count.into()),
+                (HistoryTable::Frecency, frecency.into()),
+            ])
+            .and_where(Expr::col(HistoryTable::Id).eq(history_id))
+            .to_string(SqliteQueryBuilder),
+        [],
+    )?;
+
+    Ok((visit_count, frecency))
+}
+
 pub fn save_history(history: History) -> Result<i64, Error> {
     execute_transaction(connection(), |conn| {
+        let visit_type = VisitType::from_str(history.visit_type.as_deref().unwrap_or("link"));
+
+        let now = now_ms();
+
         if let Some(id) = history.id {
             conn.execute(
                 &Query::update()
@@ -67,6 +370,7 @@ pub fn save_history(history: History) -> Result<i64, Error> {
                         (HistoryTable::Icon, history.icon.unwrap_or_default().into()),
                         (HistoryTable::Title, history.title.unwrap_or_default().into()),
                         (HistoryTable::Visit, history.visit.unwrap_or_default().into()),
+                        (HistoryTable::LastModified, now.into()),
                     ])
                     .and_where(Expr::col(HistoryTable::Id).eq(id))
                     .to_string(SqliteQueryBuilder),
@@ -74,6 +378,7 @@ pub fn save_history(history: History) -> Result<i64, Error> {
             )?;
             Ok(id)
         } else {
+            let guid = crate::store::sync::generate_guid();
             conn.execute(
                 &Query::insert()
                     .into_table(HistoryTable::Table)
@@ -82,21 +387,275 @@ pub fn save_history(history: History) -> Result<i64, Error> {
                         HistoryTable::Icon,
                         HistoryTable::Title,
                         HistoryTable::Visit,
+                        HistoryTable::Guid,
+                        HistoryTable::LastModified,
                     ])
                     .values_panic([
                         history.url.unwrap_or_default().into(),
                         history.icon.unwrap_or_default().into(),
                         history.title.unwrap_or_default().into(),
                         history.visit.unwrap_or_default().into(),
+                        guid.into(),
+                        now.into(),
                     ])
                     .to_string(SqliteQueryBuilder),
                 [],
             )?;
-            Ok(conn.last_insert_rowid())
+            let id = conn.last_insert_rowid();
+            record_visit_and_recompute(conn, id, visit_type)?;
+            Ok(id)
         }
     })
 }
 
+/// 单次导航事件的结构化观测数据，用于构建 highlights/top-sites
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryObservation {
+    pub history_id: i64,
+    pub visit_type: String,
+    pub document_type: Option<String>,
+    pub referrer_url: Option<String>,
+    pub dwell_time_ms: Option<i64>,
+    pub search_term: Option<String>,
+}
+
+/// 记录一次导航的结构化元数据，并在同一事务内同步更新访问次数与 frecency
+pub fn record_observation(obs: HistoryObservation) -> Result<i64, Error> {
+    execute_transaction(connection(), |conn| {
+        let visit_type = VisitType::from_str(&obs.visit_type);
+        record_visit_and_recompute(conn, obs.history_id, visit_type)?;
+
+        let document_type = DocumentType::from_str(obs.document_type.as_deref().unwrap_or("regular"));
+
+        conn.execute(
+            &Query::insert()
+                .into_table(HistoryMetadataTable::Table)
+                .columns([
+                    HistoryMetadataTable::HistoryId,
+                    HistoryMetadataTable::VisitTime,
+                    HistoryMetadataTable::VisitType,
+                    HistoryMetadataTable::DocumentType,
+                    HistoryMetadataTable::ReferrerUrl,
+                    HistoryMetadataTable::DwellTimeMs,
+                    HistoryMetadataTable::SearchTerm,
+                ])
+                .values_panic([
+                    obs.history_id.into(),
+                    now_ms().into(),
+                    visit_type.as_str().into(),
+                    document_type.as_str().into(),
+                    obs.referrer_url.unwrap_or_default().into(),
+                    obs.dwell_time_ms.unwrap_or(0).into(),
+                    obs.search_term.unwrap_or_default().into(),
+                ])
+                .to_string(SqliteQueryBuilder),
+            [],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    })
+}
+
+/// 按 dwell time 与访问新旧程度加权排序的高亮结果
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryHighlightResult {
+    pub history: History,
+    pub score: f64,
+}
+
+/// 返回长时间停留且访问较新的页面，作为 "highlights"/"top sites" 的候选集
+pub fn query_highlights(limit: Option<i32>) -> Result<Vec<HistoryHighlightResult>, Error> {
+    execute_simple(connection(), |conn| {
+        let sql = Query::select()
+            .columns([HistoryMetadataTable::HistoryId, HistoryMetadataTable::VisitTime, HistoryMetadataTable::DwellTimeMs])
+            .from(HistoryMetadataTable::Table)
+            .to_string(SqliteQueryBuilder);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            let history_id: i64 = row.get(0)?;
+            let visit_time: i64 = row.get(1)?;
+            let dwell_time_ms: i64 = row.get(2)?;
+            Ok((history_id, visit_time, dwell_time_ms))
+        })?;
+
+        let now = now_ms();
+        let mut scores: std::collections::HashMap<i64, f64> = std::collections::HashMap::new();
+        for row in rows {
+            let (history_id, visit_time, dwell_time_ms) = row?;
+            let age_days = (now - visit_time).max(0) / (24 * 60 * 60 * 1000);
+            let weight = age_bucket_points(age_days) / 100.0;
+            *scores.entry(history_id).or_insert(0.0) += dwell_time_ms as f64 * weight;
+        }
+
+        let mut ranked: Vec<(i64, f64)> = scores.into_iter().filter(|(_, score)| *score > 0.0).collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(limit.unwrap_or(10).max(0) as usize);
+
+        let mut results = Vec::new();
+        for (history_id, score) in ranked {
+            let sql = Query::select()
+                .columns(HISTORY_COLUMNS)
+                .from(HistoryTable::Table)
+                .and_where(Expr::col(HistoryTable::Id).eq(history_id))
+                .to_string(SqliteQueryBuilder);
+
+            if let Ok(history) = conn.query_row(&sql, [], read_history) {
+                results.push(HistoryHighlightResult { history, score });
+            }
+        }
+        Ok(results)
+    })
+}
+
+pub(crate) const HISTORY_COLUMNS: [HistoryTable; 9] = [
+    HistoryTable::Id,
+    HistoryTable::Url,
+    HistoryTable::Icon,
+    HistoryTable::Title,
+    HistoryTable::Visit,
+    HistoryTable::VisitCount,
+    HistoryTable::Frecency,
+    HistoryTable::Guid,
+    HistoryTable::LastModified,
+];
+
+pub(crate) fn read_history(row: &rusqlite::Row) -> rusqlite::Result<History> {
+    Ok(History {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        icon: row.get(2)?,
+        title: row.get(3)?,
+        visit: row.get(4)?,
+        visit_type: None,
+        visit_count: row.get(5)?,
+        frecency: row.get(6)?,
+        guid: row.get(7)?,
+        last_modified: row.get(8)?,
+    })
+}
+
+/// 按 frecency 倒序返回最常/最近访问的 URL，用于地址栏自动补全
+pub fn query_history_frecent(req: HistoryFrecentReq) -> Result<Vec<History>, Error> {
+    execute_simple(connection(), |conn| {
+        let mut query = Query::select();
+        query
+            .columns(HISTORY_COLUMNS)
+            .from(HistoryTable::Table)
+            .and_where(Expr::col(HistoryTable::Frecency).gt(0));
+
+        if let Some(q) = req.query.as_ref().filter(|q| !q.is_empty()) {
+            query.and_where(
+                Expr::col(HistoryTable::Url)
+                    .like(format!("%{}%", q))
+                    .or(Expr::col(HistoryTable::Title).like(format!("%{}%", q))),
+            );
+        }
+
+        query
+            .order_by(HistoryTable::Frecency, Order::Desc)
+            .limit(req.limit.unwrap_or(10).max(0) as u64);
+
+        let sql = query.to_string(SqliteQueryBuilder);
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], read_history)?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    })
+}
+
+/// FTS5 搜索结果，附带 bm25 相关度得分（越大越相关）
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySearchResult {
+    pub history: History,
+    pub score: f64,
+}
+
+/// 基于 FTS5 的历史记录搜索，支持前缀匹配并按相关度排序
+pub fn search_history_fts(query: String, limit: Option<i32>) -> Result<Vec<HistorySearchResult>, Error> {
+    execute_simple(connection(), |conn| {
+        let match_query = fts_prefix_query(&query);
+        let table = HistoryTable::Table.to_string();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT h.id, h.url, h.icon, h.title, h.visit, h.visit_count, h.frecency, h.guid, h.last_modified, bm25(history_fts) AS rank
+             FROM history_fts
+             JOIN {table} h ON h.id = history_fts.rowid
+             WHERE history_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2"
+        ))?;
+
+        let rows = stmt.query_map(rusqlite::params![&match_query, limit.unwrap_or(20)], |row| {
+            Ok(HistorySearchResult {
+                history: read_history(row)?,
+                score: -row.get::<_, f64>(9)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    })
+}
+
+/// 批量重算所有 URL 的 frecency，用于维护任务（如升级旧数据）
+pub fn recompute_all_frecency() -> Result<i64, Error> {
+    execute_transaction(connection(), |conn| {
+        let sql = Query::select()
+            .column(HistoryTable::Id)
+            .from(HistoryTable::Table)
+            .to_string(SqliteQueryBuilder);
+
+        let ids: Vec<i64> = {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+            let mut ids = Vec::new();
+            for row in rows {
+                ids.push(row?);
+            }
+            ids
+        };
+
+        let mut updated = 0;
+        for id in ids {
+            let visit_count: i64 = conn.query_row(
+                &Query::select()
+                    .expr(Expr::col(HistoryVisitTable::Id).count())
+                    .from(HistoryVisitTable::Table)
+                    .and_where(Expr::col(HistoryVisitTable::HistoryId).eq(id))
+                    .to_string(SqliteQueryBuilder),
+                [],
+                |row| row.get(0),
+            )?;
+            let frecency = compute_frecency(conn, id, visit_count)?;
+            conn.execute(
+                &Query::update()
+                    .table(HistoryTable::Table)
+                    .values([
+                        (HistoryTable::VisitCount, visit_count.into()),
+                        (HistoryTable::Frecency, frecency.into()),
+                    ])
+                    .and_where(Expr::col(HistoryTable::Id).eq(id))
+                    .to_string(SqliteQueryBuilder),
+                [],
+            )?;
+            updated += 1;
+        }
+
+        Ok(updated)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,6 +674,11 @@ mod tests {
             icon: Some("icon".to_string()),
             title: Some("Example Site".to_string()),
             visit: Some("2024-01-01".to_string()),
+            visit_type: Some("typed".to_string()),
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
         };
         let history_id = save_history(history.clone()).unwrap();
 
@@ -126,7 +690,152 @@ mod tests {
             icon: history.icon,
             title: Some("Updated Title".to_string()),
             visit: Some("2024-01-02".to_string()),
+            visit_type: None,
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
         };
         save_history(updated_data).unwrap();
     }
+
+    #[test]
+    fn test_frecency_typed_beats_reload() {
+        BASE_PATH.get().or_else(|| {
+            BASE_PATH.set("/tmp/browser-core/database".to_string()).ok();
+            None
+        });
+        init_history_database().expect("Failed to initialize database");
+
+        let typed = save_history(History {
+            id: None,
+            url: Some("https://typed.example.com".to_string()),
+            icon: None,
+            title: None,
+            visit: None,
+            visit_type: Some("typed".to_string()),
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
+        })
+        .unwrap();
+
+        let reload = save_history(History {
+            id: None,
+            url: Some("https://reload.example.com".to_string()),
+            icon: None,
+            title: None,
+            visit: None,
+            visit_type: Some("reload".to_string()),
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
+        })
+        .unwrap();
+
+        let results = query_history_frecent(HistoryFrecentReq {
+            query: None,
+            limit: Some(50),
+        })
+        .unwrap();
+
+        let typed_entry = results.iter().find(|h| h.id == Some(typed));
+        assert!(typed_entry.is_some(), "typed visit should have non-zero frecency");
+        assert!(!results.iter().any(|h| h.id == Some(reload)), "reload-only visit should have frecency 0");
+    }
+
+    #[test]
+    fn test_record_observation_and_highlights() {
+        BASE_PATH.get().or_else(|| {
+            BASE_PATH.set("/tmp/browser-core/database".to_string()).ok();
+            None
+        });
+        init_history_database().expect("Failed to initialize database");
+
+        let history_id = save_history(History {
+            id: None,
+            url: Some("https://highlight.example.com".to_string()),
+            icon: None,
+            title: Some("Highlighted Page".to_string()),
+            visit: None,
+            visit_type: Some("typed".to_string()),
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
+        })
+        .unwrap();
+
+        record_observation(HistoryObservation {
+            history_id,
+            visit_type: "typed".to_string(),
+            document_type: Some("regular".to_string()),
+            referrer_url: Some("https://referrer.example.com".to_string()),
+            dwell_time_ms: Some(60_000),
+            search_term: None,
+        })
+        .unwrap();
+
+        let highlights = query_highlights(Some(10)).unwrap();
+        assert!(highlights.iter().any(|h| h.history.id == Some(history_id) && h.score > 0.0));
+    }
+
+    #[test]
+    fn test_search_history_fts_prefix_match_and_ranking() {
+        BASE_PATH.get().or_else(|| {
+            BASE_PATH.set("/tmp/browser-core/database".to_string()).ok();
+            None
+        });
+        init_history_database().expect("Failed to initialize database");
+
+        let unique = now_ms();
+        save_history(History {
+            id: None,
+            url: Some("https://history-fts-a.example.com".to_string()),
+            icon: None,
+            title: Some(format!("Rustlang {unique} Tutorial")),
+            visit: None,
+            visit_type: None,
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
+        })
+        .unwrap();
+        save_history(History {
+            id: None,
+            url: Some("https://history-fts-b.example.com".to_string()),
+            icon: None,
+            title: Some(format!("Rustlang {unique} Rustlang {unique}")),
+            visit: None,
+            visit_type: None,
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
+        })
+        .unwrap();
+        save_history(History {
+            id: None,
+            url: Some("https://history-fts-c.example.com".to_string()),
+            icon: None,
+            title: Some("Totally Unrelated".to_string()),
+            visit: None,
+            visit_type: None,
+            visit_count: None,
+            frecency: None,
+            guid: None,
+            last_modified: None,
+        })
+        .unwrap();
+
+        let results = search_history_fts(format!("Rustl {unique}"), None).unwrap();
+        let matches: Vec<(usize, f64)> = results
+            .iter()
+            .map(|r| (r.history.title.as_deref().unwrap_or("").matches(&unique.to_string()).count(), r.score))
+            .collect();
+        crate::store::assert_fts_prefix_match_and_ranking(&matches);
+    }
 }
@@ -0,0 +1,375 @@
+use anyhow::Error;
+use napi_derive::napi;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+
+use crate::store::bookmark::{self, BookmarkTable};
+use crate::store::history::{self, HistoryTable};
+use crate::store::sync::{generate_guid, now_ms};
+use crate::store::{execute_transaction, recompute_all_frecency};
+
+/// WebKit (Chrome) 时间戳的纪元是 1601-01-01，与 Unix 纪元相差的微秒数
+const WEBKIT_EPOCH_OFFSET_US: i64 = 11_644_473_600_000_000;
+
+/// 导入时为一个 URL 合成的 history_visit 行数上限。recompute_all_frecency 会把
+/// history.visit_count 重算为 COUNT(history_visit)，而 compute_frecency 本身也只
+/// 采样最近 10 次访问，因此超过此上限的外部 visit_count 没有必要逐条还原为真实行，
+/// 按上限合成即可，既让 visit_count 与 history_visit 的行数保持一致，又避免为
+/// 来源浏览器里访问次数极高的 URL 插入海量行。
+const MAX_SYNTHESIZED_VISITS_PER_IMPORT: i64 = 50;
+
+/// 外部浏览器数据来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    Chrome,
+    Firefox,
+}
+
+impl ImportSource {
+    fn from_str(s: &str) -> ImportSource {
+        match s {
+            "firefox" => ImportSource::Firefox,
+            _ => ImportSource::Chrome,
+        }
+    }
+}
+
+/// 导入结果：成功导入与因重复/无效而跳过的行数
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub imported: i64,
+    pub skipped: i64,
+}
+
+fn open_source(path: &str) -> Result<Connection, Error> {
+    Ok(Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?)
+}
+
+fn chrome_epoch_to_millis(webkit_us: i64) -> i64 {
+    (webkit_us - WEBKIT_EPOCH_OFFSET_US) / 1000
+}
+
+struct ForeignHistoryRow {
+    url: String,
+    title: String,
+    visit_count: i64,
+    last_visit_ms: i64,
+}
+
+fn read_chrome_history(conn: &Connection) -> Result<Vec<ForeignHistoryRow>, Error> {
+    let mut stmt = conn.prepare("SELECT url, title, visit_count, last_visit_time FROM urls")?;
+    let rows = stmt.query_map([], |row| {
+        let webkit_us: i64 = row.get(3)?;
+        Ok(ForeignHistoryRow {
+            url: row.get(0)?,
+            title: row.get(1)?,
+            visit_count: row.get(2)?,
+            last_visit_ms: chrome_epoch_to_millis(webkit_us),
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(row?);
+    }
+    Ok(result)
+}
+
+fn read_firefox_history(conn: &Connection) -> Result<Vec<ForeignHistoryRow>, Error> {
+    let mut stmt = conn.prepare(
+        "SELECT p.url, p.title, p.visit_count, MAX(v.visit_date)
+         FROM moz_places p
+         LEFT JOIN moz_historyvisits v ON v.place_id = p.id
+         GROUP BY p.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let last_visit_us: Option<i64> = row.get(3)?;
+        Ok(ForeignHistoryRow {
+            url: row.get(0)?,
+            title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            visit_count: row.get(2)?,
+            last_visit_ms: last_visit_us.unwrap_or(0) / 1000,
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(row?);
+    }
+    Ok(result)
+}
+
+/// 导入历史记录：按 URL 与现有记录去重，已存在则合并 visit_count 而非重复插入
+pub fn import_history(path: String, source: String) -> Result<ImportResult, Error> {
+    let foreign = open_source(&path)?;
+    let rows = match ImportSource::from_str(&source) {
+        ImportSource::Chrome => read_chrome_history(&foreign)?,
+        ImportSource::Firefox => read_firefox_history(&foreign)?,
+    };
+    drop(foreign);
+
+    let table = HistoryTable::Table.to_string();
+    let mut imported = 0i64;
+    let mut skipped = 0i64;
+
+    execute_transaction(history::connection(), |conn| {
+        for row in &rows {
+            if row.url.is_empty() {
+                skipped += 1;
+                continue;
+            }
+
+            let existing: Option<(i64, i64)> = conn
+                .query_row(&format!("SELECT id, visit_count FROM {table} WHERE url = ?1"), [&row.url], |r| {
+                    Ok((r.get(0)?, r.get(1)?))
+                })
+                .ok();
+
+            let synthesized_visits = row.visit_count.clamp(1, MAX_SYNTHESIZED_VISITS_PER_IMPORT);
+
+            match existing {
+                Some((existing_id, existing_count)) => {
+                    conn.execute(
+                        &format!("UPDATE {table} SET visit_count = ?1, last_modified = ?2 WHERE url = ?3"),
+                        rusqlite::params![existing_count + synthesized_visits, now_ms(), row.url],
+                    )?;
+                    // 按合并进来的访问次数逐条合成 history_visit 记录（而不是只记一条），
+                    // 使 recompute_all_frecency 基于 history_visit 计数重建 visit_count 时
+                    // 得到的结果与上面刚合并写入的值一致，而不会被清零或错算
+                    for _ in 0..synthesized_visits {
+                        conn.execute(
+                            "INSERT INTO history_visit (history_id, visit_time, visit_type) VALUES (?1, ?2, 'link')",
+                            rusqlite::params![existing_id, row.last_visit_ms],
+                        )?;
+                    }
+                    skipped += 1;
+                }
+                None => {
+                    conn.execute(
+                        &format!(
+                            "INSERT INTO {table} (url, icon, title, visit, visit_count, frecency, guid, last_modified) \
+                             VALUES (?1, '', ?2, '', ?3, 0, ?4, ?5)"
+                        ),
+                        rusqlite::params![row.url, row.title, synthesized_visits, generate_guid(), now_ms()],
+                    )?;
+                    let history_id = conn.last_insert_rowid();
+                    for _ in 0..synthesized_visits {
+                        conn.execute(
+                            "INSERT INTO history_visit (history_id, visit_time, visit_type) VALUES (?1, ?2, 'link')",
+                            rusqlite::params![history_id, row.last_visit_ms],
+                        )?;
+                    }
+                    imported += 1;
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    recompute_all_frecency()?;
+
+    Ok(ImportResult { imported, skipped })
+}
+
+struct ForeignBookmarkRow {
+    name: String,
+    url: String,
+}
+
+fn read_chrome_bookmarks(conn: &Connection) -> Result<Vec<ForeignBookmarkRow>, Error> {
+    let mut stmt = conn.prepare("SELECT title, url FROM bookmarks WHERE url IS NOT NULL")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ForeignBookmarkRow {
+            name: row.get(0)?,
+            url: row.get(1)?,
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(row?);
+    }
+    Ok(result)
+}
+
+fn read_firefox_bookmarks(conn: &Connection) -> Result<Vec<ForeignBookmarkRow>, Error> {
+    let mut stmt = conn.prepare(
+        "SELECT b.title, p.url
+         FROM moz_bookmarks b
+         JOIN moz_places p ON p.id = b.fk
+         WHERE b.type = 1",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ForeignBookmarkRow {
+            name: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+            url: row.get(1)?,
+        })
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(row?);
+    }
+    Ok(result)
+}
+
+/// 导入书签：按 URL 与现有记录去重，已存在同 URL 的书签则跳过
+pub fn import_bookmarks(path: String, source: String) -> Result<ImportResult, Error> {
+    let foreign = open_source(&path)?;
+    let rows = match ImportSource::from_str(&source) {
+        ImportSource::Chrome => read_chrome_bookmarks(&foreign)?,
+        ImportSource::Firefox => read_firefox_bookmarks(&foreign)?,
+    };
+    drop(foreign);
+
+    let table = BookmarkTable::Table.to_string();
+    let mut imported = 0i64;
+    let mut skipped = 0i64;
+
+    execute_transaction(bookmark::connection(), |conn| {
+        for row in &rows {
+            if row.url.is_empty() {
+                skipped += 1;
+                continue;
+            }
+
+            let exists: bool = conn
+                .query_row(&format!("SELECT 1 FROM {table} WHERE url = ?1"), [&row.url], |_| Ok(()))
+                .is_ok();
+
+            if exists {
+                skipped += 1;
+                continue;
+            }
+
+            conn.execute(
+                &format!(
+                    "INSERT INTO {table} (sort, folder, parent, url, name, icon, date, guid, last_modified) \
+                     VALUES (0, 0, 0, ?1, ?2, '', ?3, ?4, ?5)"
+                ),
+                rusqlite::params![row.url, row.name, now_ms(), generate_guid(), now_ms()],
+            )?;
+            imported += 1;
+        }
+        Ok(())
+    })?;
+
+    // import_bookmarks 直接对书签表执行原始 SQL，不经过 save_bookmark，因此需要显式让书签缓存
+    // 失效，否则已加载的缓存会让导入的书签在下次保存/删除前一直不可见
+    bookmark::invalidate_bookmark_cache_if_loaded()?;
+
+    Ok(ImportResult { imported, skipped })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{bookmark, history};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Once;
+
+    fn init() {
+        static INIT: Once = Once::new();
+
+        INIT.call_once(|| {
+            std::fs::create_dir_all("/tmp/browser-core/database").expect("Failed to create test directory");
+            crate::store::BASE_PATH.set("/tmp/browser-core/database".to_string()).ok();
+        });
+
+        bookmark::init_bookmark_database().expect("Failed to initialize bookmark database");
+        history::init_history_database().expect("Failed to initialize history database");
+    }
+
+    fn unique_path(prefix: &str) -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        format!("/tmp/browser-core/{prefix}-{n}.sqlite")
+    }
+
+    fn make_chrome_history_db(rows: &[(&str, &str, i64, i64)]) -> String {
+        let path = unique_path("chrome-history");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE urls (url TEXT, title TEXT, visit_count INTEGER, last_visit_time INTEGER)")
+            .unwrap();
+        for (url, title, visit_count, last_visit_time) in rows {
+            conn.execute(
+                "INSERT INTO urls (url, title, visit_count, last_visit_time) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![url, title, visit_count, last_visit_time],
+            )
+            .unwrap();
+        }
+        path
+    }
+
+    fn make_chrome_bookmarks_db(rows: &[(&str, &str)]) -> String {
+        let path = unique_path("chrome-bookmarks");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE bookmarks (title TEXT, url TEXT)").unwrap();
+        for (title, url) in rows {
+            conn.execute("INSERT INTO bookmarks (title, url) VALUES (?1, ?2)", rusqlite::params![title, url])
+                .unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_import_history_inserts_then_merges_existing_url() {
+        init();
+
+        // WebKit(1601纪元) 时间戳，转换后对应一个合理的 Unix 毫秒值
+        let webkit_ts = WEBKIT_EPOCH_OFFSET_US + 1_000_000;
+        let url = format!("https://import-history-{}.example.com", now_ms());
+
+        let first = make_chrome_history_db(&[(&url, "First Import", 3, webkit_ts)]);
+        let result = import_history(first, "chrome".to_string()).unwrap();
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.skipped, 0);
+
+        let row: (i64, i64) = history::connection()
+            .get()
+            .unwrap()
+            .query_row("SELECT visit_count, last_modified FROM history WHERE url = ?1", [&url], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(row.0, 3);
+        let first_last_modified = row.1;
+
+        // 第二次导入同一个 URL 应该合并 visit_count 而不是插入重复行
+        let second = make_chrome_history_db(&[(&url, "First Import", 2, webkit_ts)]);
+        let result = import_history(second, "chrome".to_string()).unwrap();
+        assert_eq!(result.imported, 0);
+        assert_eq!(result.skipped, 1);
+
+        let row: (i64, i64, i64) = history::connection()
+            .get()
+            .unwrap()
+            .query_row(
+                "SELECT visit_count, last_modified, (SELECT COUNT(*) FROM history_visit WHERE history_id = history.id) FROM history WHERE url = ?1",
+                [&url],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(row.0, 5, "visit_count should merge 3 + 2");
+        assert!(row.1 >= first_last_modified, "last_modified should advance on merge");
+        assert!(row.2 > 0, "merge branch must still log a history_visit row for recompute consistency");
+    }
+
+    #[test]
+    fn test_import_bookmarks_skips_existing_url() {
+        init();
+
+        let url = format!("https://import-bookmark-{}.example.com", now_ms());
+
+        let first = make_chrome_bookmarks_db(&[("First Title", &url)]);
+        let result = import_bookmarks(first, "chrome".to_string()).unwrap();
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.skipped, 0);
+
+        let second = make_chrome_bookmarks_db(&[("Second Title", &url), ("", "")]);
+        let result = import_bookmarks(second, "chrome".to_string()).unwrap();
+        assert_eq!(result.imported, 0, "duplicate and empty URLs must both be skipped, not imported");
+        assert_eq!(result.skipped, 2);
+    }
+}
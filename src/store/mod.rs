@@ -1,16 +1,24 @@
 use anyhow::Error;
 use napi_derive::napi;
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{OnceLock, RwLock};
 
+pub mod backup;
 pub mod bookmark;
 pub mod download;
 pub mod favicon;
 pub mod history;
+pub mod import;
+pub mod sync;
 
+pub use backup::*;
 pub use bookmark::*;
+pub use download::*;
 pub use history::*;
+pub use import::{import_bookmarks, import_history, ImportResult};
+pub use sync::{apply_remote, changes_since, SyncRecord};
 
 /// 通用的获取请求结构
 #[napi(object)]
@@ -26,6 +34,7 @@ pub struct DeleteReq {
     pub id: i64,
     pub force: Option<bool>,   // 强制删除标志
     pub cascade: Option<bool>, // 级联删除标志
+    pub reason: Option<String>, // 操作原因，记录到变更日志
 }
 
 // 基础路径的全局存储
@@ -41,45 +50,75 @@ pub fn init(db_path: &str) -> Result<(), Error> {
 
     init_bookmark_database()?;
     init_history_database()?;
+    init_download_database()?;
 
     Ok(())
 }
 
-pub fn open_conn(db_path: &str) -> Result<Arc<Mutex<Connection>>, Error> {
-    let conn = Connection::open(db_path)?;
-    Ok(Arc::new(Mutex::new(conn)))
+/// 将用户输入切分为逐词前缀匹配的 FTS5 查询（如 "down report" -> "down"* "report"*），
+/// 供书签/历史/下载三张表的 FTS5 搜索共用
+pub(crate) fn fts_prefix_query(raw: &str) -> String {
+    raw.split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-pub fn query_simple<F, R>(
-    conn: &Arc<Mutex<Connection>>,
-    query: F,
-) -> Result<R, Error>
+/// 每个数据库连接池的默认最大连接数
+pub(crate) const DEFAULT_POOL_MAX_SIZE: u32 = 8;
+
+fn build_raw_pool(db_path: &str, max_size: u32) -> Result<r2d2::Pool<SqliteConnectionManager>, Error> {
+    let manager = SqliteConnectionManager::file(db_path).with_init(|conn| conn.execute_batch("PRAGMA journal_mode=WAL;"));
+    Ok(r2d2::Pool::builder().max_size(max_size).build(manager)?)
+}
+
+/// 单个数据库的连接池，所有模块共用同一套读写语义；内部用 `RwLock` 包裹以支持 `reset`
+/// 在恢复（restore）数据库文件后让已初始化的连接池重新指向磁盘上的最新文件
+pub struct DbPool(RwLock<r2d2::Pool<SqliteConnectionManager>>);
+
+impl DbPool {
+    fn get(&self) -> Result<r2d2::PooledConnection<SqliteConnectionManager>, Error> {
+        Ok(self.0.read().unwrap().get()?)
+    }
+
+    /// 丢弃当前连接池并基于同一路径重新打开一个新的连接池
+    pub(crate) fn reset(&self, db_path: &str, max_size: u32) -> Result<(), Error> {
+        let pool = build_raw_pool(db_path, max_size)?;
+        *self.0.write().unwrap() = pool;
+        Ok(())
+    }
+}
+
+pub fn open_conn(db_path: &str) -> Result<DbPool, Error> {
+    open_conn_with_size(db_path, DEFAULT_POOL_MAX_SIZE)
+}
+
+/// 创建一个可配置连接数上限的连接池，并为每个新连接开启 WAL 模式以支持并发读
+pub fn open_conn_with_size(db_path: &str, max_size: u32) -> Result<DbPool, Error> {
+    Ok(DbPool(RwLock::new(build_raw_pool(db_path, max_size)?)))
+}
+
+pub fn query_simple<F, R>(pool: &DbPool, query: F) -> Result<R, Error>
 where
     F: FnOnce(&Connection) -> Result<R, Error>,
 {
-    let conn = conn.lock().unwrap();
+    let conn = pool.get()?;
     query(&conn)
 }
 
-pub fn execute_simple<F, R>(
-    conn: &Arc<Mutex<Connection>>,
-    operation: F,
-) -> Result<R, Error>
+pub fn execute_simple<F, R>(pool: &DbPool, operation: F) -> Result<R, Error>
 where
     F: FnOnce(&Connection) -> Result<R, Error>,
 {
-    let conn = conn.lock().unwrap();
+    let conn = pool.get()?;
     operation(&conn)
 }
 
-pub fn execute_transaction<F, R>(
-    conn: &Arc<Mutex<Connection>>,
-    operation: F,
-) -> Result<R, Error>
+pub fn execute_transaction<F, R>(pool: &DbPool, operation: F) -> Result<R, Error>
 where
     F: FnOnce(&Connection) -> Result<R, Error>,
 {
-    let conn = conn.lock().unwrap();
+    let conn = pool.get()?;
     let tx = conn.unchecked_transaction()?;
 
     match operation(&conn) {
@@ -93,3 +132,20 @@ where
         }
     }
 }
+
+/// 书签/历史/下载三张表的 FTS5 前缀匹配 + 排序测试共用的断言：`matches` 按搜索结果顺序给出
+/// `(唯一标记在该行中出现的次数, 相关度得分)`；要求恰好命中两条包含唯一标记的行，
+/// 且关键词重复出现次数更多的行排在前面、得分不低于另一行
+#[cfg(test)]
+pub(crate) fn assert_fts_prefix_match_and_ranking(matches: &[(usize, f64)]) {
+    assert_eq!(matches.len(), 2, "only the two rows containing the unique marker should match");
+    assert!(matches.iter().all(|(occurrences, _)| *occurrences > 0), "every match must contain the unique marker");
+    assert!(
+        matches[0].0 > matches[1].0,
+        "the row with more keyword occurrences should rank first"
+    );
+    assert!(
+        matches[0].1 >= matches[1].1,
+        "the first-ranked row should have the higher relevance score"
+    );
+}
@@ -0,0 +1,457 @@
+use anyhow::Error;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use napi_derive::napi;
+use rand::RngCore;
+use rusqlite::Connection;
+use sea_query::*;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::store::bookmark::{self, Bookmark, BookmarkTable, BOOKMARK_COLUMNS};
+use crate::store::history::{self, History, HistoryTable, HISTORY_COLUMNS};
+use crate::store::{execute_simple, execute_transaction};
+
+pub(crate) fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// 生成一个 12 字符的 base64 随机 GUID，作为跨设备同步的稳定外部标识
+pub(crate) fn generate_guid() -> String {
+    let mut bytes = [0u8; 9];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[derive(Iden)]
+enum TombstoneTable {
+    Table,
+    Id,
+    Entity,
+    Guid,
+    DeletedAt,
+}
+
+fn ensure_tombstone_table(conn: &Connection) -> Result<(), Error> {
+    conn.execute(
+        &Table::create()
+            .table(TombstoneTable::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(TombstoneTable::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(TombstoneTable::Entity).text().not_null())
+            .col(ColumnDef::new(TombstoneTable::Guid).text().not_null())
+            .col(ColumnDef::new(TombstoneTable::DeletedAt).big_integer().not_null())
+            .to_string(SqliteQueryBuilder),
+        [],
+    )?;
+    Ok(())
+}
+
+/// 在给定连接所在的数据库中记录一条删除墓碑，供同步端感知该 guid 已被删除
+pub(crate) fn record_tombstone(conn: &Connection, entity: &str, guid: &str) -> Result<(), Error> {
+    ensure_tombstone_table(conn)?;
+    conn.execute(
+        &Query::insert()
+            .into_table(TombstoneTable::Table)
+            .columns([TombstoneTable::Entity, TombstoneTable::Guid, TombstoneTable::DeletedAt])
+            .values_panic([entity.into(), guid.into(), now_ms().into()])
+            .to_string(SqliteQueryBuilder),
+        [],
+    )?;
+    Ok(())
+}
+
+/// 自上次同步时间点以来的增量记录，创建/更新与删除通过 `deleted` 区分
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub entity: String,
+    pub guid: String,
+    pub deleted: bool,
+    pub last_modified: i64,
+    pub bookmark: Option<Bookmark>,
+    pub history: Option<History>,
+}
+
+fn tombstones_since(conn: &Connection, entity: &str, timestamp: i64) -> Result<Vec<SyncRecord>, Error> {
+    ensure_tombstone_table(conn)?;
+    let sql = Query::select()
+        .columns([TombstoneTable::Guid, TombstoneTable::DeletedAt])
+        .from(TombstoneTable::Table)
+        .and_where(Expr::col(TombstoneTable::Entity).eq(entity))
+        .and_where(Expr::col(TombstoneTable::DeletedAt).gt(timestamp))
+        .to_string(SqliteQueryBuilder);
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row| {
+        let guid: String = row.get(0)?;
+        let deleted_at: i64 = row.get(1)?;
+        Ok(SyncRecord {
+            entity: entity.to_string(),
+            guid,
+            deleted: true,
+            last_modified: deleted_at,
+            bookmark: None,
+            history: None,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
+/// 返回自 `timestamp` 以来所有书签/历史的新增、更新与删除记录
+pub fn changes_since(timestamp: i64) -> Result<Vec<SyncRecord>, Error> {
+    let mut records = Vec::new();
+
+    execute_simple(bookmark::connection(), |conn| {
+        let sql = Query::select()
+            .columns(BOOKMARK_COLUMNS)
+            .from(BookmarkTable::Table)
+            .and_where(Expr::col(BookmarkTable::LastModified).gt(timestamp))
+            .to_string(SqliteQueryBuilder);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], bookmark::read_bookmark)?;
+        for row in rows {
+            let bookmark = row?;
+            records.push(SyncRecord {
+                entity: "bookmark".to_string(),
+                guid: bookmark.guid.clone(),
+                deleted: false,
+                last_modified: bookmark.last_modified,
+                bookmark: Some(bookmark),
+                history: None,
+            });
+        }
+
+        records.extend(tombstones_since(conn, "bookmark", timestamp)?);
+        Ok(())
+    })?;
+
+    execute_simple(history::connection(), |conn| {
+        let sql = Query::select()
+            .columns(HISTORY_COLUMNS)
+            .from(HistoryTable::Table)
+            .and_where(Expr::col(HistoryTable::LastModified).gt(timestamp))
+            .to_string(SqliteQueryBuilder);
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], history::read_history)?;
+        for row in rows {
+            let history = row?;
+            records.push(SyncRecord {
+                entity: "history".to_string(),
+                guid: history.guid.clone().unwrap_or_default(),
+                deleted: false,
+                last_modified: history.last_modified.unwrap_or(0),
+                bookmark: None,
+                history: Some(history),
+            });
+        }
+
+        records.extend(tombstones_since(conn, "history", timestamp)?);
+        Ok(())
+    })?;
+
+    Ok(records)
+}
+
+fn local_last_modified(conn: &Connection, table: &str, guid: &str) -> Result<Option<i64>, Error> {
+    let result: Option<i64> = conn
+        .query_row(&format!("SELECT last_modified FROM {table} WHERE guid = ?1"), [guid], |row| row.get(0))
+        .ok();
+    Ok(result)
+}
+
+fn apply_bookmark_record(conn: &Connection, record: &SyncRecord) -> Result<(), Error> {
+    let table = BookmarkTable::Table.to_string();
+
+    if record.deleted {
+        if let Some(local_ts) = local_last_modified(conn, &table, &record.guid)? {
+            if local_ts >= record.last_modified {
+                // 本地版本更新，按 last-writer-wins 丢弃该远程墓碑
+                return Ok(());
+            }
+        }
+        conn.execute(&format!("DELETE FROM {table} WHERE guid = ?1"), [&record.guid])?;
+        record_tombstone(conn, "bookmark", &record.guid)?;
+        return Ok(());
+    }
+
+    let Some(data) = record.bookmark.as_ref() else {
+        return Ok(());
+    };
+
+    if let Some(local_ts) = local_last_modified(conn, &table, &record.guid)? {
+        if local_ts >= record.last_modified {
+            // 本地版本更新，按 last-writer-wins 丢弃该远程记录
+            return Ok(());
+        }
+        conn.execute(
+            &format!(
+                "UPDATE {table} SET sort = ?1, folder = ?2, parent = ?3, url = ?4, name = ?5, icon = ?6, date = ?7, last_modified = ?8 WHERE guid = ?9"
+            ),
+            rusqlite::params![
+                data.sort,
+                data.folder,
+                data.parent,
+                data.url,
+                data.name,
+                data.icon,
+                data.date,
+                record.last_modified,
+                record.guid,
+            ],
+        )?;
+    } else {
+        conn.execute(
+            &format!(
+                "INSERT INTO {table} (sort, folder, parent, url, name, icon, date, guid, last_modified) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            ),
+            rusqlite::params![
+                data.sort,
+                data.folder,
+                data.parent,
+                data.url,
+                data.name,
+                data.icon,
+                data.date,
+                record.guid,
+                record.last_modified,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn apply_history_record(conn: &Connection, record: &SyncRecord) -> Result<(), Error> {
+    let table = HistoryTable::Table.to_string();
+
+    if record.deleted {
+        if let Some(local_ts) = local_last_modified(conn, &table, &record.guid)? {
+            if local_ts >= record.last_modified {
+                // 本地版本更新，按 last-writer-wins 丢弃该远程墓碑
+                return Ok(());
+            }
+        }
+        conn.execute(&format!("DELETE FROM {table} WHERE guid = ?1"), [&record.guid])?;
+        record_tombstone(conn, "history", &record.guid)?;
+        return Ok(());
+    }
+
+    let Some(data) = record.history.as_ref() else {
+        return Ok(());
+    };
+
+    if let Some(local_ts) = local_last_modified(conn, &table, &record.guid)? {
+        if local_ts >= record.last_modified {
+            return Ok(());
+        }
+        conn.execute(
+            &format!("UPDATE {table} SET url = ?1, icon = ?2, title = ?3, visit = ?4, last_modified = ?5 WHERE guid = ?6"),
+            rusqlite::params![
+                data.url.clone().unwrap_or_default(),
+                data.icon.clone().unwrap_or_default(),
+                data.title.clone().unwrap_or_default(),
+                data.visit.clone().unwrap_or_default(),
+                record.last_modified,
+                record.guid,
+            ],
+        )?;
+    } else {
+        conn.execute(
+            &format!(
+                "INSERT INTO {table} (url, icon, title, visit, guid, last_modified) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+            ),
+            rusqlite::params![
+                data.url.clone().unwrap_or_default(),
+                data.icon.clone().unwrap_or_default(),
+                data.title.clone().unwrap_or_default(),
+                data.visit.clone().unwrap_or_default(),
+                record.guid,
+                record.last_modified,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 合并远端变更到本地存储，按 `guid` 匹配并以 `last_modified` 做 last-writer-wins 冲突解决
+pub fn apply_remote(records: Vec<SyncRecord>) -> Result<(), Error> {
+    let (bookmark_records, history_records): (Vec<_>, Vec<_>) =
+        records.into_iter().partition(|r| r.entity == "bookmark");
+
+    if !bookmark_records.is_empty() {
+        execute_transaction(bookmark::connection(), |conn| {
+            for record in &bookmark_records {
+                apply_bookmark_record(conn, record)?;
+            }
+            Ok(())
+        })?;
+        // apply_bookmark_record 直接对书签表执行原始 SQL，不经过 save_bookmark/delete_bookmark，
+        // 因此需要显式让书签缓存失效，否则缓存命中会让同步应用的变更（尤其是删除）不可见
+        bookmark::invalidate_bookmark_cache_if_loaded()?;
+    }
+
+    if !history_records.is_empty() {
+        execute_transaction(history::connection(), |conn| {
+            for record in &history_records {
+                apply_history_record(conn, record)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::bookmark::{get_bookmark, save_bookmark, BookmarkData, BookmarkDataReq};
+    use crate::store::GetReq;
+
+    fn init() {
+        use std::sync::Once;
+        static INIT: Once = Once::new();
+
+        INIT.call_once(|| {
+            std::fs::create_dir_all("/tmp/browser-core/database").expect("Failed to create test directory");
+            crate::store::BASE_PATH
+                .set("/tmp/browser-core/database".to_string())
+                .ok();
+        });
+
+        crate::store::bookmark::init_bookmark_database().expect("Failed to initialize bookmark database");
+    }
+
+    fn test_bookmark_data(url: &str) -> BookmarkData {
+        BookmarkData {
+            sort: 0,
+            folder: 0,
+            parent: 0,
+            url: url.to_string(),
+            name: "Sync Test".to_string(),
+            icon: "".to_string(),
+            date: 0,
+        }
+    }
+
+    #[test]
+    fn test_apply_remote_rejects_stale_delete_tombstone() {
+        init();
+
+        let id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: test_bookmark_data("https://sync-stale-delete.example.com"),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+        let local = get_bookmark(GetReq { id }).unwrap().unwrap();
+
+        // 远端墓碑的 last_modified 早于本地最后修改时间，按 last-writer-wins 应被丢弃
+        let stale_tombstone = SyncRecord {
+            entity: "bookmark".to_string(),
+            guid: local.guid.clone(),
+            deleted: true,
+            last_modified: local.last_modified - 1,
+            bookmark: None,
+            history: None,
+        };
+        apply_remote(vec![stale_tombstone]).unwrap();
+
+        let still_there = get_bookmark(GetReq { id }).unwrap();
+        assert!(still_there.is_some(), "newer local row must survive a stale remote delete");
+    }
+
+    #[test]
+    fn test_apply_remote_applies_newer_delete_tombstone() {
+        init();
+
+        let id = save_bookmark(BookmarkDataReq {
+            id: None,
+            data: test_bookmark_data("https://sync-fresh-delete.example.com"),
+            reason: None,
+            dedupe: None,
+        })
+        .unwrap();
+        let local = get_bookmark(GetReq { id }).unwrap().unwrap();
+
+        let fresh_tombstone = SyncRecord {
+            entity: "bookmark".to_string(),
+            guid: local.guid.clone(),
+            deleted: true,
+            last_modified: local.last_modified + 1_000,
+            bookmark: None,
+            history: None,
+        };
+        apply_remote(vec![fresh_tombstone]).unwrap();
+
+        let gone = get_bookmark(GetReq { id }).unwrap();
+        assert!(gone.is_none(), "a delete newer than the local row must be applied");
+    }
+
+    #[test]
+    fn test_apply_remote_insert_is_visible_through_warm_cache() {
+        init();
+
+        // 先触发一次 get_bookmark，确保书签缓存在本次写入之前已经被加载（warm），
+        // 这样才能验证 apply_remote 的直接写表路径会让缓存跟着刷新，而不是仅在
+        // 缓存尚未加载、走数据库回退路径时才碰巧正确
+        let _ = get_bookmark(GetReq { id: -1 }).unwrap();
+
+        let guid = generate_guid();
+        let remote_record = SyncRecord {
+            entity: "bookmark".to_string(),
+            guid: guid.clone(),
+            deleted: false,
+            last_modified: now_ms(),
+            bookmark: Some(Bookmark {
+                id: 0,
+                sort: 0,
+                folder: 0,
+                parent: 0,
+                url: "https://sync-insert-visible.example.com".to_string(),
+                name: "Synced From Remote".to_string(),
+                icon: "".to_string(),
+                date: 0,
+                guid: guid.clone(),
+                last_modified: now_ms(),
+            }),
+            history: None,
+        };
+        apply_remote(vec![remote_record]).unwrap();
+
+        let rows = crate::store::query_bookmark(crate::store::BookmarkQueryReq {
+            url: None,
+            url_prefix: None,
+            name: None,
+            folder: None,
+            parent: None,
+            after: None,
+            limit: None,
+            order_by: None,
+            order_desc: None,
+        })
+        .unwrap();
+        assert!(
+            rows.items.iter().any(|b| b.guid == guid),
+            "a bookmark inserted by apply_remote must show up through the cached query path"
+        );
+    }
+}